@@ -7,4 +7,10 @@ criterion_main!(
     benchmarks::bench_insert_std_vs_customhashmap::benches,
     benchmarks::bench_lookup_custom::benches,
     benchmarks::bench_lru_custom_compare::benches,
+    benchmarks::bench_deletion_strategy::benches,
+    benchmarks::bench_probe_strategy::benches,
+    benchmarks::bench_load_factor::benches,
+    benchmarks::bench_hasher::benches,
+    benchmarks::bench_resize_strategy::benches,
+    benchmarks::bench_perfect::benches,
 );
@@ -0,0 +1,31 @@
+use criterion::{Criterion, criterion_group};
+use custom_hashmap::{CustomHashMap, DeletionStrategy};
+use std::hint::black_box;
+
+pub fn bench_deletion_compare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deletion strategy churn");
+
+    for (name, strategy) in [
+        ("tombstone", DeletionStrategy::Tombstone),
+        ("backward shift", DeletionStrategy::BackwardShift),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut map = CustomHashMap::with_deletion_strategy(strategy);
+                // Scatter keys so the identity hash doesn't form one long run
+                let key = |i: u32| i.wrapping_mul(0x9E37_79B1);
+                for i in 0..10_000 {
+                    map.insert(key(i), i);
+                    if i >= 64 {
+                        map.remove(&key(i - 64));
+                    }
+                    black_box(map.get(&key(i / 2)));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_deletion_compare);
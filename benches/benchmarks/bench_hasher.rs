@@ -0,0 +1,60 @@
+use criterion::{Criterion, criterion_group};
+use custom_hashmap::{
+    AssemblyBuildHash, CustomHashMap, FullContentHash, KeyHasher, MultiplicativeHash,
+};
+use std::collections::hash_map::RandomState;
+use std::hint::black_box;
+
+fn insert_lookup<S: KeyHasher<u32> + Clone>(hasher: &S) {
+    let mut map = CustomHashMap::with_hasher(hasher.clone());
+    // Strided keys defeat the identity hash's low bits
+    for i in 0..1000u32 {
+        map.insert(i * 64, i);
+    }
+    for i in 0..1000u32 {
+        black_box(map.get(&(i * 64)));
+    }
+}
+
+fn insert_lookup_paths<S: KeyHasher<str> + KeyHasher<String> + Clone>(
+    hasher: &S,
+    paths: &[String],
+) {
+    let mut map = CustomHashMap::with_hasher(hasher.clone());
+    for (i, path) in paths.iter().enumerate() {
+        map.insert(path.clone(), i);
+    }
+    for path in paths {
+        black_box(map.get(path.as_str()));
+    }
+}
+
+pub fn bench_hasher_compare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hasher compare");
+
+    group.bench_function("assembly", |b| b.iter(|| insert_lookup(&AssemblyBuildHash)));
+    group.bench_function("multiplicative", |b| {
+        b.iter(|| insert_lookup(&MultiplicativeHash))
+    });
+    let random_state = RandomState::new();
+    group.bench_function("std RandomState", |b| {
+        b.iter(|| insert_lookup(&random_state))
+    });
+
+    group.bench_function("full content", |b| {
+        b.iter(|| insert_lookup(&FullContentHash))
+    });
+
+    // Keys sharing their first and last two bytes
+    let paths: Vec<String> = (0..1000).map(|i| format!("/srv/{i}/index.html")).collect();
+    group.bench_function("assembly paths", |b| {
+        b.iter(|| insert_lookup_paths(&AssemblyBuildHash, &paths))
+    });
+    group.bench_function("full content paths", |b| {
+        b.iter(|| insert_lookup_paths(&FullContentHash, &paths))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_hasher_compare);
@@ -1,16 +1,27 @@
 use criterion::{Criterion, criterion_group};
-use custom_hashmap::CustomHashMap;
+use custom_hashmap::{CustomHashMap, DeletionStrategy};
 
 pub fn insert_compare(c: &mut Criterion) {
     let mut group = c.benchmark_group("assembly hash test");
-    group.bench_function("custom Hashmap test", |b| {
-        b.iter(|| {
-            let mut map = CustomHashMap::new();
-            for i in 0..1000 {
-                map.insert(i, i);
-            }
-        })
-    });
+    for (name, strategy) in [
+        (
+            "custom Hashmap test (tombstone)",
+            DeletionStrategy::Tombstone,
+        ),
+        (
+            "custom Hashmap test (backward shift)",
+            DeletionStrategy::BackwardShift,
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut map = CustomHashMap::with_deletion_strategy(strategy);
+                for i in 0..1000 {
+                    map.insert(i, i);
+                }
+            })
+        });
+    }
     group.bench_function("std Hashmap insert test", |b| {
         b.iter(|| {
             let mut map = std::collections::HashMap::new();
@@ -0,0 +1,29 @@
+use criterion::{Criterion, criterion_group};
+use custom_hashmap::{CustomHashMap, CustomHashMapBuilder};
+use std::hint::black_box;
+
+pub fn bench_load_factor_sweep(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load factor sweep");
+
+    for load_factor in [0.5, 0.75, 0.9] {
+        let builder = CustomHashMapBuilder::new().max_load_factor(load_factor);
+        let sample: CustomHashMap<u32, u32> = builder.build();
+        let name = format!("insert+lookup lf={}", sample.max_load_factor());
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut map = builder.build();
+                for i in 0..1000u32 {
+                    map.insert(i.wrapping_mul(0x9E37_79B1), i);
+                }
+                for i in 0..1000u32 {
+                    black_box(map.get(&i.wrapping_mul(0x9E37_79B1)));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_load_factor_sweep);
@@ -1,21 +1,32 @@
 use criterion::{Criterion, criterion_group};
-use custom_hashmap::CustomHashMap;
+use custom_hashmap::{CustomHashMap, DeletionStrategy};
 use std::hint::black_box;
 
 pub fn bench_lookup_compare(c: &mut Criterion) {
     let mut group = c.benchmark_group("lookup test");
 
-    group.bench_function("custom HashMap lookup", |b| {
-        b.iter(|| {
-            let mut map = CustomHashMap::new();
-            for i in 0..1000 {
-                map.insert(i, i);
-            }
-            for i in 0..1000 {
-                black_box(map.get(&i));
-            }
-        })
-    });
+    for (name, strategy) in [
+        (
+            "custom HashMap lookup (tombstone)",
+            DeletionStrategy::Tombstone,
+        ),
+        (
+            "custom HashMap lookup (backward shift)",
+            DeletionStrategy::BackwardShift,
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut map = CustomHashMap::with_deletion_strategy(strategy);
+                for i in 0..1000 {
+                    map.insert(i, i);
+                }
+                for i in 0..1000 {
+                    black_box(map.get(&i));
+                }
+            })
+        });
+    }
 
     group.bench_function("std HashMap lookup", |b| {
         b.iter(|| {
@@ -0,0 +1,43 @@
+use criterion::{Criterion, criterion_group};
+use custom_hashmap::{CustomHashMap, PerfectHashMap};
+use std::hint::black_box;
+
+const HEADERS: [&str; 10] = [
+    "accept",
+    "authorization",
+    "cache-control",
+    "content-type",
+    "cookie",
+    "host",
+    "user-agent",
+    "referer",
+    "origin",
+    "etag",
+];
+
+pub fn bench_perfect_compare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("perfect lookup");
+
+    let perfect = PerfectHashMap::new(HEADERS.iter().enumerate().map(|(i, &h)| (h, i))).unwrap();
+    group.bench_function("perfect HashMap lookup", |b| {
+        b.iter(|| {
+            for header in HEADERS {
+                black_box(perfect.get(black_box(header)));
+            }
+        })
+    });
+
+    let custom: CustomHashMap<&str, usize> =
+        HEADERS.iter().enumerate().map(|(i, &h)| (h, i)).collect();
+    group.bench_function("custom HashMap lookup", |b| {
+        b.iter(|| {
+            for header in HEADERS {
+                black_box(custom.get(black_box(header)));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_perfect_compare);
@@ -0,0 +1,30 @@
+use criterion::{Criterion, criterion_group};
+use custom_hashmap::{CustomHashMap, ProbeStrategy};
+use std::hint::black_box;
+
+pub fn bench_probe_compare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("probe strategy clustered lookup");
+
+    for (name, strategy) in [
+        ("linear", ProbeStrategy::Linear),
+        ("robin hood", ProbeStrategy::RobinHood),
+    ] {
+        let mut map = CustomHashMap::with_probe_strategy(strategy);
+        // Multiples of 8 pile up in a few home buckets under the identity hash
+        for i in 0..1000u32 {
+            map.insert(i * 8, i);
+        }
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for i in 0..2000u32 {
+                    black_box(map.get(&(i * 4)));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_probe_compare);
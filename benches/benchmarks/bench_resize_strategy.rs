@@ -0,0 +1,59 @@
+use criterion::{Criterion, criterion_group};
+use custom_hashmap::{CustomHashMapBuilder, ResizeStrategy};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const STRATEGIES: [(&str, ResizeStrategy); 2] = [
+    ("immediate", ResizeStrategy::Immediate),
+    ("incremental", ResizeStrategy::Incremental { min_batch: 32 }),
+];
+
+pub fn bench_resize_compare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resize strategy 100k inserts");
+
+    for (name, strategy) in STRATEGIES {
+        let builder = CustomHashMapBuilder::new().resize_strategy(strategy);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut map = builder.build();
+                for i in 0..100_000u32 {
+                    map.insert(i.wrapping_mul(0x9E37_79B1), i);
+                }
+                black_box(map.len())
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// Reports the slowest single insert of each 100k-insert run instead of the
+/// run's total, which is the number incremental resizing is meant to bound
+pub fn bench_resize_slowest_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resize strategy slowest insert");
+
+    for (name, strategy) in STRATEGIES {
+        let builder = CustomHashMapBuilder::new().resize_strategy(strategy);
+        group.bench_function(name, |b| {
+            b.iter_custom(|iters| {
+                let mut total = Duration::ZERO;
+                for _ in 0..iters {
+                    let mut map = builder.build();
+                    let mut worst = Duration::ZERO;
+                    for i in 0..100_000u32 {
+                        let start = Instant::now();
+                        map.insert(i.wrapping_mul(0x9E37_79B1), i);
+                        worst = worst.max(start.elapsed());
+                    }
+                    black_box(map.len());
+                    total += worst;
+                }
+                total
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_resize_compare, bench_resize_slowest_insert);
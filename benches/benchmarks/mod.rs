@@ -1,4 +1,10 @@
+pub mod bench_deletion_strategy;
+pub mod bench_hasher;
 pub mod bench_insert_std_vs_customhashmap;
+pub mod bench_load_factor;
 pub mod bench_lookup_custom;
 pub mod bench_lru_custom_compare;
+pub mod bench_perfect;
+pub mod bench_probe_strategy;
+pub mod bench_resize_strategy;
 pub mod std_hashmap_my_hashmap;
@@ -7,7 +7,7 @@ use custom_hashmap::AssemblyHash;
 pub fn assembly_hash_compare(c: &mut Criterion) {
     let mut group = c.benchmark_group("assembly hash test");
     group.bench_function("std Hashmap test", |b| {
-        b.iter(|| std::collections::HashMap::<u32, u32>::new());
+        b.iter(std::collections::HashMap::<u32, u32>::new);
     });
     group.bench_function("std Hash", |b| {
         b.iter(|| {
@@ -0,0 +1,152 @@
+//! Rust source generation for static perfect hash tables.
+//!
+//! Meant for a downstream `build.rs`: describe the keywords and the value
+//! expression for each, then write the generated source to `OUT_DIR` and
+//! `include!` it. The output is a `static` array and a lookup function, so
+//! nothing is built at startup and lookups never allocate.
+//!
+//! The generated function hashes with the same first/last-two-bytes hash as
+//! [`AssemblyHash`](crate::AssemblyHash) for `&str`, written out in plain Rust
+//! so the output depends on nothing but `core`. Keywords the hash can't tell
+//! apart, such as any two shorter than 4 bytes, make generation fail with
+//! [`PerfectHashError::HashCollision`].
+
+use std::fmt::{self, Write};
+
+use crate::hash::AssemblyBuildHash;
+use crate::perfect::{PerfectHashError, PerfectHashMap, SearchBounds};
+
+/// Generator for a static keyword table and its lookup function
+///
+/// Values are Rust expressions of `value_type`, which must be `Copy` and
+/// usable in a `static`, such as enum variants or integers.
+#[derive(Debug, Clone)]
+pub struct LookupTable {
+    value_type: String,
+    fn_name: String,
+    static_name: String,
+    bounds: SearchBounds,
+    entries: Vec<(String, String)>,
+}
+
+impl LookupTable {
+    /// Starts an empty table generating `static TABLE` and `pub fn lookup`
+    pub fn new(value_type: impl Into<String>) -> Self {
+        Self {
+            value_type: value_type.into(),
+            fn_name: "lookup".to_string(),
+            static_name: "TABLE".to_string(),
+            bounds: SearchBounds::default(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Name of the generated lookup function
+    pub fn fn_name(mut self, name: impl Into<String>) -> Self {
+        self.fn_name = name.into();
+        self
+    }
+
+    /// Name of the generated `static`, to keep several tables in one module apart
+    pub fn static_name(mut self, name: impl Into<String>) -> Self {
+        self.static_name = name.into();
+        self
+    }
+
+    /// Limits for the seed search, as in [`PerfectHashMap::with_bounds`]
+    pub fn bounds(mut self, bounds: SearchBounds) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    /// Maps `keyword` to the Rust expression `value`
+    pub fn entry(mut self, keyword: impl Into<String>, value: impl Into<String>) -> Self {
+        self.entries.push((keyword.into(), value.into()));
+        self
+    }
+
+    pub fn entries<I, K, V>(mut self, entries: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.entries
+            .extend(entries.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Searches for a perfect seed and renders the table and lookup function.
+    /// Errors refer to entries by the order they were added in.
+    pub fn generate(&self) -> Result<String, PerfectHashError> {
+        let map = PerfectHashMap::with_bounds(
+            self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+            AssemblyBuildHash,
+            self.bounds,
+        )?;
+
+        let mut out = String::new();
+        self.render(&map, &mut out)
+            .expect("writing to a String can't fail");
+        Ok(out)
+    }
+
+    fn render(&self, map: &PerfectHashMap<&str, &str>, out: &mut String) -> fmt::Result {
+        let Self {
+            value_type,
+            fn_name,
+            static_name,
+            ..
+        } = self;
+        let slots = map.slots();
+
+        writeln!(
+            out,
+            "// @generated by custom_hashmap::codegen. Do not edit."
+        )?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "static {static_name}: [Option<(&str, {value_type})>; {}] = [",
+            slots.len()
+        )?;
+        for slot in slots {
+            match slot {
+                Some((keyword, value)) => writeln!(out, "    Some(({keyword:?}, {value})),")?,
+                None => writeln!(out, "    None,")?,
+            }
+        }
+        writeln!(out, "];")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "pub fn {fn_name}(key: &str) -> Option<{value_type}> {{"
+        )?;
+        // The hash of `look_up_identifier`, spelled out for any target
+        out.push_str(
+            "    let bytes = key.as_bytes();\n\
+             \x20   let len = bytes.len();\n\
+             \x20   let hash = if len < 4 {\n\
+             \x20       80\n\
+             \x20   } else {\n\
+             \x20       let head = u16::from_le_bytes([bytes[0], bytes[1]]) as u32;\n\
+             \x20       let tail = u16::from_le_bytes([bytes[len - 2], bytes[len - 1]]) as u32;\n\
+             \x20       head << 16 | tail\n\
+             \x20   };\n",
+        );
+        writeln!(
+            out,
+            "    let idx = ((hash as u64).wrapping_mul({:#x}) >> {}) as usize;",
+            map.seed(),
+            map.shift()
+        )?;
+        writeln!(out, "    match {static_name}[idx] {{")?;
+        out.push_str(
+            "        Some((keyword, value)) if keyword == key => Some(value),\n\
+             \x20       _ => None,\n\
+             \x20   }\n\
+             }\n",
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+//! Control-byte groups for [`CustomHashMap`](crate::CustomHashMap).
+//!
+//! Every slot has one control byte: `EMPTY`, `DELETED`, or a 7-bit fragment
+//! of the key's hash with the top bit set. Probing loads `GROUP_WIDTH`
+//! control bytes at once and turns them into bitmasks, so a single compare
+//! filters out almost every non-matching slot before any key is touched.
+//!
+//! `EMPTY` is zero, so a fresh control array is zeroed memory, which the
+//! allocator can hand out without writing it: a large table's pages are only
+//! cleared as entries first reach them.
+
+/// Number of control bytes inspected per probe step
+pub(crate) const GROUP_WIDTH: usize = 16;
+
+pub(crate) const EMPTY: u8 = 0b0000_0000;
+pub(crate) const DELETED: u8 = 0b0000_0001;
+
+/// Whether a control byte marks its slot full, i.e. holds a hash fragment
+#[inline]
+pub(crate) fn is_full(ctrl: u8) -> bool {
+    ctrl & 0x80 != 0
+}
+
+/// Top 7 bits of a mixed hash, stored in the control byte of a full slot.
+/// The raw assembly hash of a small integer has all-zero high bits, so it is
+/// spread with a Fibonacci multiply first.
+#[inline]
+pub(crate) fn h2(hash: u32) -> u8 {
+    0x80 | (hash.wrapping_mul(0x9E37_79B1) >> 25) as u8
+}
+
+/// One bit per control byte in a group, lowest bit for the first byte
+#[derive(Clone, Copy)]
+pub(crate) struct BitMask(pub(crate) u16);
+
+impl BitMask {
+    #[inline]
+    pub(crate) fn any(self) -> bool {
+        self.0 != 0
+    }
+
+    #[inline]
+    pub(crate) fn lowest(self) -> Option<usize> {
+        self.any().then(|| self.0.trailing_zeros() as usize)
+    }
+
+    /// Keeps only the bits below `bit`
+    #[inline]
+    pub(crate) fn below(self, bit: usize) -> Self {
+        BitMask(self.0 & ((1u32 << bit) - 1) as u16)
+    }
+}
+
+impl Iterator for BitMask {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        let bit = self.lowest()?;
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+mod imp {
+    use super::{BitMask, EMPTY, GROUP_WIDTH};
+    use std::arch::x86_64::{
+        __m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8, _mm_set1_epi8,
+    };
+
+    /// `GROUP_WIDTH` control bytes held in an SSE2 register
+    #[derive(Clone, Copy)]
+    pub(crate) struct Group(__m128i);
+
+    impl Group {
+        #[inline]
+        pub(crate) fn load(ctrl: &[u8]) -> Self {
+            assert!(ctrl.len() >= GROUP_WIDTH);
+            // SAFETY: the assert guarantees 16 readable bytes, and loadu has
+            // no alignment requirement
+            unsafe { Group(_mm_loadu_si128(ctrl.as_ptr().cast())) }
+        }
+
+        #[inline]
+        pub(crate) fn match_byte(self, byte: u8) -> BitMask {
+            // SAFETY: SSE2 is statically enabled for this cfg
+            unsafe {
+                let cmp = _mm_cmpeq_epi8(self.0, _mm_set1_epi8(byte as i8));
+                BitMask(_mm_movemask_epi8(cmp) as u16)
+            }
+        }
+
+        #[inline]
+        pub(crate) fn match_empty(self) -> BitMask {
+            self.match_byte(EMPTY)
+        }
+
+        /// Only full slots have the top bit set, which movemask collects
+        /// directly
+        #[inline]
+        pub(crate) fn match_empty_or_deleted(self) -> BitMask {
+            // SAFETY: SSE2 is statically enabled for this cfg
+            unsafe { BitMask(!_mm_movemask_epi8(self.0) as u16) }
+        }
+    }
+}
+
+#[cfg(not(all(target_arch = "x86_64", target_feature = "sse2")))]
+mod imp {
+    use super::{BitMask, EMPTY, GROUP_WIDTH};
+
+    /// Portable fallback that compares the control bytes one at a time
+    #[derive(Clone, Copy)]
+    pub(crate) struct Group([u8; GROUP_WIDTH]);
+
+    impl Group {
+        #[inline]
+        pub(crate) fn load(ctrl: &[u8]) -> Self {
+            let mut bytes = [0; GROUP_WIDTH];
+            bytes.copy_from_slice(&ctrl[..GROUP_WIDTH]);
+            Group(bytes)
+        }
+
+        #[inline]
+        fn mask(self, pred: impl Fn(u8) -> bool) -> BitMask {
+            let mut bits = 0u16;
+            for (i, &byte) in self.0.iter().enumerate() {
+                if pred(byte) {
+                    bits |= 1 << i;
+                }
+            }
+            BitMask(bits)
+        }
+
+        #[inline]
+        pub(crate) fn match_byte(self, byte: u8) -> BitMask {
+            self.mask(|b| b == byte)
+        }
+
+        #[inline]
+        pub(crate) fn match_empty(self) -> BitMask {
+            self.match_byte(EMPTY)
+        }
+
+        #[inline]
+        pub(crate) fn match_empty_or_deleted(self) -> BitMask {
+            self.mask(|b| b & 0x80 == 0)
+        }
+    }
+}
+
+pub(crate) use imp::Group;
@@ -1,4 +1,25 @@
-#[cfg(target_os = "linux")]
+use std::borrow::{Borrow, Cow};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::{Chain, FusedIterator, Zip};
+use std::mem::MaybeUninit;
+use std::ops::Index;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::group::{DELETED, EMPTY, GROUP_WIDTH, Group, h2, is_full};
+
+/// Builds a hash from the first two and last two bytes of an identifier.
+/// Inputs shorter than 4 bytes hash to 80.
+///
+/// Uses inline assembly on x86_64 and aarch64 when the `asm` feature is on,
+/// and [`look_up_identifier_portable`] everywhere else. Both give the same
+/// result for every input.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes.
+#[cfg(all(feature = "asm", target_arch = "x86_64"))]
 #[inline(always)]
 pub unsafe fn look_up_identifier(data: *const u8, len: usize) -> u32 {
     unsafe {
@@ -16,53 +37,220 @@ pub unsafe fn look_up_identifier(data: *const u8, len: usize) -> u32 {
             "3:",
             in("rdi") data,
             in("rsi") len,
-            lateout("eax") result,
-            clobber_abi("system")
+            out("eax") result,
+            out("edx") _,
+            options(pure, readonly, nostack)
         );
         result
     }
 }
 
-#[cfg(target_os = "macos")]
+/// Builds a hash from the first two and last two bytes of an identifier.
+/// Inputs shorter than 4 bytes hash to 80.
+///
+/// Uses inline assembly on x86_64 and aarch64 when the `asm` feature is on,
+/// and [`look_up_identifier_portable`] everywhere else. Both give the same
+/// result for every input.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes.
+#[cfg(all(feature = "asm", target_arch = "aarch64"))]
 #[inline(always)]
 pub unsafe fn look_up_identifier(data: *const u8, len: usize) -> u32 {
     unsafe {
         let result: u32;
+        // Every register is an operand, so none of them can alias an input
+        // that is still to be read
         std::arch::asm!(
             "cmp {len}, #4",
             "b.lo 2f",
-            "ldrh w0, [{data}]",
-            "sub x1, {len}, #2",
-            "ldrh w2, [{data}, x1]",
-            "lsl w0, w0, #16",
-            "orr w0, w0, w2",
+            "ldrh {result:w}, [{data}]",
+            "add {end}, {data}, {len}",
+            "ldurh {tail:w}, [{end}, #-2]",
+            "orr {result:w}, {tail:w}, {result:w}, lsl #16",
             "b 3f",
             "2:",
-            "mov w0, #80",
+            "mov {result:w}, #80",
             "3:",
             data = in(reg) data,
             len = in(reg) len,
-            lateout("w0") result,
-            clobber_abi("system")
+            result = out(reg) result,
+            end = out(reg) _,
+            tail = out(reg) _,
+            options(pure, readonly, nostack)
         );
         result
     }
 }
 
+/// Builds a hash from the first two and last two bytes of an identifier.
+/// Inputs shorter than 4 bytes hash to 80.
+///
+/// This target has no assembly version, or the `asm` feature is off, so
+/// this forwards to [`look_up_identifier_portable`].
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes.
+#[cfg(not(all(feature = "asm", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+#[inline(always)]
+pub unsafe fn look_up_identifier(data: *const u8, len: usize) -> u32 {
+    if len < 4 {
+        // Like the assembly versions, touch nothing: `data` may be null or
+        // dangling when `len` is 0
+        return 80;
+    }
+    // SAFETY: the caller guarantees `len` readable bytes, and `len` >= 4
+    // rules out null and dangling pointers
+    look_up_identifier_portable(unsafe { std::slice::from_raw_parts(data, len) })
+}
+
+/// Pure-Rust reference for [`look_up_identifier`]: the first two bytes as a
+/// little-endian `u16` in the high half, the last two in the low half, or 80
+/// below 4 bytes
+#[inline]
+pub fn look_up_identifier_portable(data: &[u8]) -> u32 {
+    match *data {
+        [a, b, .., c, d] => {
+            (u16::from_le_bytes([a, b]) as u32) << 16 | u16::from_le_bytes([c, d]) as u32
+        }
+        _ => 80,
+    }
+}
+
+/// Safe [`look_up_identifier`] over a byte slice, the hash every string and
+/// byte key uses under [`AssemblyHash`]
+#[inline]
+pub fn assembly_hash_bytes(data: &[u8]) -> u32 {
+    // SAFETY: a slice is valid for reads of its whole length
+    unsafe { look_up_identifier(data.as_ptr(), data.len()) }
+}
+
 /// Trait for types that can be hashed using the assembly hash function
+///
+/// Lookups on [`CustomHashMap`] accept any `Q` where `K: Borrow<Q>`, so an
+/// owned type and its borrowed forms must hash identically: `String`, `str`
+/// and `&str` all produce the same value for the same text. The same goes for
+/// the byte types and `[u8]`, and for the shared string types and `str`.
 pub trait AssemblyHash {
     fn assembly_hash(&self) -> u32;
+
+    /// Hash over the whole value, used by [`FullContentHash`]. Strings mix in
+    /// every byte; the default scrambles [`assembly_hash`](Self::assembly_hash),
+    /// which already covers all of an integer.
+    fn full_content_hash(&self) -> u32 {
+        fold(mum(self.assembly_hash() as u64 ^ MIX_SEED, MIX_PRIME))
+    }
+}
+
+impl AssemblyHash for [u8] {
+    fn assembly_hash(&self) -> u32 {
+        assembly_hash_bytes(self)
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        hash_bytes(self)
+    }
+}
+
+impl AssemblyHash for &[u8] {
+    fn assembly_hash(&self) -> u32 {
+        (**self).assembly_hash()
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        (**self).full_content_hash()
+    }
+}
+
+impl AssemblyHash for Vec<u8> {
+    fn assembly_hash(&self) -> u32 {
+        self.as_slice().assembly_hash()
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        self.as_slice().full_content_hash()
+    }
+}
+
+impl<const N: usize> AssemblyHash for [u8; N] {
+    fn assembly_hash(&self) -> u32 {
+        self.as_slice().assembly_hash()
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        self.as_slice().full_content_hash()
+    }
+}
+
+impl AssemblyHash for str {
+    fn assembly_hash(&self) -> u32 {
+        self.as_bytes().assembly_hash()
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        self.as_bytes().full_content_hash()
+    }
 }
 
 impl AssemblyHash for String {
     fn assembly_hash(&self) -> u32 {
         self.as_str().assembly_hash()
     }
+
+    fn full_content_hash(&self) -> u32 {
+        self.as_str().full_content_hash()
+    }
 }
 
 impl AssemblyHash for &str {
     fn assembly_hash(&self) -> u32 {
-        unsafe { look_up_identifier(self.as_ptr(), self.len()) }
+        (**self).assembly_hash()
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        (**self).full_content_hash()
+    }
+}
+
+impl AssemblyHash for Box<str> {
+    fn assembly_hash(&self) -> u32 {
+        (**self).assembly_hash()
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        (**self).full_content_hash()
+    }
+}
+
+impl AssemblyHash for Rc<str> {
+    fn assembly_hash(&self) -> u32 {
+        (**self).assembly_hash()
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        (**self).full_content_hash()
+    }
+}
+
+impl AssemblyHash for Arc<str> {
+    fn assembly_hash(&self) -> u32 {
+        (**self).assembly_hash()
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        (**self).full_content_hash()
+    }
+}
+
+impl AssemblyHash for Cow<'_, str> {
+    fn assembly_hash(&self) -> u32 {
+        (**self).assembly_hash()
+    }
+
+    fn full_content_hash(&self) -> u32 {
+        (**self).full_content_hash()
     }
 }
 
@@ -103,214 +291,2066 @@ impl AssemblyHash for isize {
     }
 }
 
-/// Entry in the hash table bucket
-enum BucketEntry<K, V> {
-    Occupied(K, V),
-    Removed,
+const MIX_SEED: u64 = 0xA076_1D64_78BD_642F;
+const MIX_PRIME: u64 = 0xE703_7ED1_A0B4_28DB;
+
+/// wyhash's folded multiply: the full 128-bit product with its halves xored
+#[inline]
+fn mum(a: u64, b: u64) -> u64 {
+    let product = a as u128 * b as u128;
+    (product >> 64) as u64 ^ product as u64
+}
+
+#[inline]
+fn fold(hash: u64) -> u32 {
+    (hash >> 32) as u32 ^ hash as u32
+}
+
+/// wyhash-style mix of every byte, eight at a time. The length goes into
+/// the seed so inputs differing only in trailing zero bytes stay apart.
+fn hash_bytes(bytes: &[u8]) -> u32 {
+    let mut hash = mum(bytes.len() as u64 ^ MIX_SEED, MIX_PRIME);
+    let mut chunks = bytes.chunks_exact(8);
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        hash = mum(hash ^ word, MIX_PRIME);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut word = [0; 8];
+        word[..tail.len()].copy_from_slice(tail);
+        hash = mum(hash ^ u64::from_le_bytes(word), MIX_PRIME);
+    }
+    fold(mum(hash, MIX_SEED))
+}
+
+/// Hash strategy for a [`CustomHashMap`]: maps a key to the 32-bit hash the
+/// table probes with
+///
+/// Lookups hash the borrowed query type `Q`, so like [`AssemblyHash`] a
+/// hasher must give a key and its borrowed forms the same value. Every
+/// [`std::hash::BuildHasher`] is a `KeyHasher`, folded down to 32 bits.
+pub trait KeyHasher<Q: ?Sized> {
+    fn hash_key(&self, key: &Q) -> u32;
+}
+
+/// The default strategy, hashing keys with [`AssemblyHash::assembly_hash`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AssemblyBuildHash;
+
+impl<Q: AssemblyHash + ?Sized> KeyHasher<Q> for AssemblyBuildHash {
+    #[inline]
+    fn hash_key(&self, key: &Q) -> u32 {
+        key.assembly_hash()
+    }
+}
+
+/// Fibonacci hashing on top of [`AssemblyHash`]
+///
+/// Integers hash to themselves under the assembly hash, so keys that share
+/// their low bits (multiples of 1024, say) all get the same home slot. This
+/// multiplies by 2^64 / φ and keeps the well-mixed high half instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MultiplicativeHash;
+
+impl<Q: AssemblyHash + ?Sized> KeyHasher<Q> for MultiplicativeHash {
+    #[inline]
+    fn hash_key(&self, key: &Q) -> u32 {
+        ((key.assembly_hash() as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as u32
+    }
+}
+
+/// Hashes every byte of a string key through
+/// [`AssemblyHash::full_content_hash`]
+///
+/// The default hash reads only the first and last two bytes of a string, so
+/// keys sharing both ends (URLs, file paths, `configuration` and
+/// `coordination`) all collide. This costs a pass over the key but spreads
+/// such keys out; integer keys get a scrambled version of their value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FullContentHash;
+
+impl<Q: AssemblyHash + ?Sized> KeyHasher<Q> for FullContentHash {
+    #[inline]
+    fn hash_key(&self, key: &Q) -> u32 {
+        key.full_content_hash()
+    }
+}
+
+impl<Q: Hash + ?Sized, B: BuildHasher> KeyHasher<Q> for B {
+    #[inline]
+    fn hash_key(&self, key: &Q) -> u32 {
+        fold(self.hash_one(key))
+    }
+}
+
+/// Contents of a full slot. Whether a slot is full, empty or a tombstone is
+/// tracked by its control byte.
+struct Bucket<K, V> {
+    key: K,
+    value: V,
+    /// Full hash of `key`, so probes reject mismatches without calling
+    /// `K::eq`, resizes never rehash, and the probe distance needn't be stored
+    hash: u32,
+}
+
+impl<K, V> Bucket<K, V> {
+    /// How many slots past its home bucket the entry sits when at `idx` in a
+    /// table of `cap` slots
+    fn dist(&self, idx: usize, cap: usize) -> usize {
+        idx.wrapping_sub(self.hash as usize) & (cap - 1)
+    }
 }
 
-impl<K: Clone, V: Clone> Clone for BucketEntry<K, V> {
+impl<K: Clone, V: Clone> Clone for Bucket<K, V> {
     fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            value: self.value.clone(),
+            hash: self.hash,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.key.clone_from(&source.key);
+        self.value.clone_from(&source.value);
+        self.hash = source.hash;
+    }
+}
+
+/// Error returned by [`CustomHashMap::try_reserve`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The requested capacity doesn't fit in `usize` or exceeds `isize::MAX` bytes
+    CapacityOverflow,
+    /// The allocator couldn't provide the memory
+    AllocError,
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            BucketEntry::Occupied(k, v) => BucketEntry::Occupied(k.clone(), v.clone()),
-            BucketEntry::Removed => BucketEntry::Removed,
+            TryReserveError::CapacityOverflow => f.write_str("capacity overflow"),
+            TryReserveError::AllocError => f.write_str("memory allocation failed"),
         }
     }
 }
 
-/// Custom HashMap using open addressing with linear probing and the assembly hash
-pub struct CustomHashMap<K, V> {
-    buckets: Vec<Option<BucketEntry<K, V>>>,
-    len: usize,
+impl std::error::Error for TryReserveError {}
+
+impl From<std::collections::TryReserveError> for TryReserveError {
+    fn from(_: std::collections::TryReserveError) -> Self {
+        // Byte-size overflow is ruled out before allocating, so this is an allocator failure
+        TryReserveError::AllocError
+    }
 }
 
-impl<K, V> CustomHashMap<K, V>
-where
-    K: Eq + AssemblyHash + Clone,
-{
-    pub fn new() -> Self {
-        Self::with_capacity(16)
+/// How [`CustomHashMap`] frees a slot when an entry is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeletionStrategy {
+    /// Mark the slot deleted so probes skip over it. Removal is cheap, but
+    /// markers lengthen probes until the next rehash clears them.
+    #[default]
+    Tombstone,
+    /// Shift the rest of the probe cluster back into the freed slot, so the
+    /// table never holds markers and lookups stay as short as after a rehash.
+    BackwardShift,
+}
+
+/// How [`CustomHashMap`] orders entries within a probe cluster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProbeStrategy {
+    /// Plain linear probing: a new entry takes the first free slot.
+    #[default]
+    Linear,
+    /// Robin Hood hashing: an insert displaces any entry sitting closer to its
+    /// home bucket than the incoming one, which keeps probe distances even and
+    /// lets lookups stop as soon as they pass an entry with a shorter distance.
+    /// Always deletes by [`DeletionStrategy::BackwardShift`].
+    RobinHood,
+}
+
+impl ProbeStrategy {
+    /// Under Robin Hood ordering a key can't sit past an entry that is closer
+    /// to its own home, so meeting one ends the probe early
+    fn stops_probe(self, slot_dist: usize, probe_dist: usize) -> bool {
+        self == ProbeStrategy::RobinHood && slot_dist < probe_dist
     }
+}
 
-    pub fn with_capacity(capacity: usize) -> Self {
-        let cap = capacity.max(1).next_power_of_two();
-        let mut buckets = Vec::with_capacity(cap);
-        for _ in 0..cap {
-            buckets.push(None);
+/// When [`CustomHashMap`] moves its entries into a grown table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeStrategy {
+    /// Rehash every entry inside the insert that triggers growth.
+    #[default]
+    Immediate,
+    /// Keep the old table next to the new one and move at least `min_batch`
+    /// of its slots over on every insert or removal, so no single operation
+    /// pays for the whole rehash. Lookups check both tables until the move
+    /// completes. Compacting tombstones goes the same way, into a new table
+    /// of the same size.
+    ///
+    /// `min_batch` is a floor: each operation moves more slots when that is
+    /// what it takes to empty the old table before the map holds as many
+    /// entries as it had slots, and so well before the next growth. The new
+    /// table is allocated without being written, so its memory is only
+    /// touched as entries land in it.
+    Incremental { min_batch: usize },
+}
+
+/// Resize policy and strategies, fixed when the map is built
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Policy {
+    max_load_factor: f64,
+    tombstone_threshold: f64,
+    growth_multiplier: usize,
+    deletion: DeletionStrategy,
+    probe: ProbeStrategy,
+    resize: ResizeStrategy,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            max_load_factor: 0.75,
+            tombstone_threshold: 0.25,
+            growth_multiplier: 2,
+            deletion: DeletionStrategy::default(),
+            probe: ProbeStrategy::default(),
+            resize: ResizeStrategy::default(),
         }
-        Self { buckets, len: 0 }
     }
+}
 
-    fn bucket_index(&self, key: &K) -> usize {
-        let hash = key.assembly_hash() as usize;
-        hash % self.buckets.len()
+impl Policy {
+    /// Entries plus tombstones a table of `slots` holds before it grows. One
+    /// slot always stays empty so every probe has somewhere to stop.
+    fn grow_at(&self, slots: usize) -> usize {
+        ((slots as f64 * self.max_load_factor) as usize).min(slots - 1)
     }
 
-    fn find_slot(&self, key: &K) -> (usize, Option<usize>) {
-        let mut idx = self.bucket_index(key);
-        let first_removed = None;
+    /// Tombstones a table of `slots` holds before it is rehashed in place
+    fn compact_at(&self, slots: usize) -> usize {
+        ((slots as f64 * self.tombstone_threshold).ceil() as usize).max(1)
+    }
 
-        loop {
-            match &self.buckets[idx] {
-                None => return (idx, first_removed),
-                Some(BucketEntry::Removed) => return (idx, Some(idx)),
-                Some(BucketEntry::Occupied(k, _)) if k == key => return (idx, first_removed),
-                _ => {}
-            }
-            idx = (idx + 1) % self.buckets.len();
+    /// Smallest power-of-two slot count that holds `capacity` entries
+    fn slots_for(&self, capacity: usize) -> Option<usize> {
+        let estimate = (capacity as f64 / self.max_load_factor).ceil() as usize;
+        let mut slots = estimate.max(1).checked_next_power_of_two()?;
+        while self.grow_at(slots) < capacity {
+            slots = slots.checked_mul(2)?;
         }
+        Some(slots)
     }
+}
 
-    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        self.ensure_capacity();
+/// Configures and builds a [`CustomHashMap`]
+///
+/// Every setting can be read back from the built map, e.g. through
+/// [`CustomHashMap::max_load_factor`], so benchmarks can sweep them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CustomHashMapBuilder {
+    initial_capacity: usize,
+    policy: Policy,
+    /// Whether `policy.deletion` was chosen by the caller rather than left
+    /// at its default, so RobinHood can reject an explicit `Tombstone`
+    deletion_set: bool,
+}
 
-        let (idx, first_removed) = self.find_slot(&key);
+impl Default for CustomHashMapBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        // If key exists, update value
-        if let Some(BucketEntry::Occupied(k, v)) = &mut self.buckets[idx]
-            && k == &key
-        {
-            return Some(std::mem::replace(v, value));
+impl CustomHashMapBuilder {
+    /// Starts from the defaults used by [`CustomHashMap::new`]: room for 12
+    /// entries (16 slots), 75% load factor, compaction at 25% tombstones and
+    /// 2x growth
+    pub fn new() -> Self {
+        Self {
+            initial_capacity: 12,
+            policy: Policy::default(),
+            deletion_set: false,
         }
+    }
 
-        // Insert at first removed slot or the found slot
-        let slot = first_removed.unwrap_or(idx);
-        self.buckets[slot] = Some(BucketEntry::Occupied(key, value));
-        self.len += 1;
-        None
+    /// Number of entries the map holds before it first grows, as in
+    /// [`CustomHashMap::with_capacity`]. The slot count is sized from it
+    /// with the load factor in effect when the map is built.
+    pub fn initial_capacity(mut self, capacity: usize) -> Self {
+        self.initial_capacity = capacity;
+        self
     }
 
-    pub fn get(&self, key: &K) -> Option<&V> {
-        let mut idx = self.bucket_index(key);
-        let start_idx = idx;
+    /// Fraction of slots that may hold entries or tombstones before the
+    /// table grows
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `0 < load_factor < 1`.
+    pub fn max_load_factor(mut self, load_factor: f64) -> Self {
+        assert!(
+            load_factor > 0.0 && load_factor < 1.0,
+            "max load factor must be between 0 and 1, got {load_factor}"
+        );
+        self.policy.max_load_factor = load_factor;
+        self
+    }
 
-        loop {
-            match &self.buckets[idx] {
-                None => break,
-                Some(BucketEntry::Removed) => {}
-                Some(BucketEntry::Occupied(k, v)) if k == key => return Some(v),
-                Some(_) => {}
-            }
-            idx = (idx + 1) % self.buckets.len();
-            if idx == start_idx {
-                break;
-            }
-        }
+    /// Fraction of slots that may hold tombstones before the table is
+    /// rehashed at its current size
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `0 < threshold <= 1`.
+    pub fn tombstone_threshold(mut self, threshold: f64) -> Self {
+        assert!(
+            threshold > 0.0 && threshold <= 1.0,
+            "tombstone threshold must be in (0, 1], got {threshold}"
+        );
+        self.policy.tombstone_threshold = threshold;
+        self
+    }
 
-        None
+    /// Factor the slot count is multiplied by on growth. The slot count is
+    /// always a power of two, so the factor must be one too.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `multiplier` is a power of two of at least 2.
+    pub fn growth_multiplier(mut self, multiplier: usize) -> Self {
+        assert!(
+            multiplier >= 2 && multiplier.is_power_of_two(),
+            "growth multiplier must be a power of two of at least 2, got {multiplier}"
+        );
+        self.policy.growth_multiplier = multiplier;
+        self
     }
 
-    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
-        let mut idx = self.bucket_index(key);
-        let start_idx = idx;
-
-        for _ in 0..self.buckets.len() {
-            if let Some(BucketEntry::Occupied(k, _)) = &self.buckets[idx] {
-                if k == key {
-                    // SAFETY: We're at a valid index and the key matches
-                    // We can safely return a mutable reference since we won't
-                    // access this bucket again in this function
-                    return match &mut self.buckets[idx] {
-                        Some(BucketEntry::Occupied(_, v)) => Some(v),
-                        _ => None,
-                    };
-                }
-            } else if self.buckets[idx].is_none() {
-                break;
-            }
-            idx = (idx + 1) % self.buckets.len();
-            if idx == start_idx {
-                break;
-            }
+    pub fn deletion_strategy(mut self, deletion: DeletionStrategy) -> Self {
+        self.policy.deletion = deletion;
+        self.deletion_set = true;
+        self
+    }
+
+    /// With [`ProbeStrategy::RobinHood`] removals always shift back, so the
+    /// deletion strategy defaults to [`DeletionStrategy::BackwardShift`].
+    pub fn probe_strategy(mut self, probe: ProbeStrategy) -> Self {
+        self.policy.probe = probe;
+        self
+    }
+
+    /// # Panics
+    ///
+    /// Panics if an incremental `min_batch` is 0.
+    pub fn resize_strategy(mut self, resize: ResizeStrategy) -> Self {
+        if let ResizeStrategy::Incremental { min_batch } = resize {
+            assert!(
+                min_batch > 0,
+                "incremental resize min_batch must be at least 1"
+            );
         }
+        self.policy.resize = resize;
+        self
+    }
 
-        None
+    pub fn build<K, V>(self) -> CustomHashMap<K, V> {
+        self.build_with_hasher(AssemblyBuildHash)
     }
 
-    pub fn remove(&mut self, key: &K) -> Option<V> {
-        let mut idx = self.bucket_index(key);
-        let start_idx = idx;
+    /// Builds a map that hashes its keys with `hasher`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot count for the initial capacity overflows `usize`,
+    /// or if [`DeletionStrategy::Tombstone`] was set explicitly together with
+    /// [`ProbeStrategy::RobinHood`], which cannot leave tombstones.
+    pub fn build_with_hasher<K, V, S>(self, hasher: S) -> CustomHashMap<K, V, S> {
+        assert!(
+            !(self.deletion_set
+                && self.policy.probe == ProbeStrategy::RobinHood
+                && self.policy.deletion == DeletionStrategy::Tombstone),
+            "RobinHood probing requires BackwardShift deletion, got Tombstone"
+        );
+        let slots = self
+            .policy
+            .slots_for(self.initial_capacity)
+            .expect("capacity overflow");
+        CustomHashMap::with_policy(slots, self.policy, hasher)
+    }
+}
 
-        loop {
-            match &self.buckets[idx] {
-                None => break,
-                Some(BucketEntry::Removed) => {}
-                Some(BucketEntry::Occupied(k, _)) if k == key => {
-                    self.len -= 1;
-                    // Take ownership and mark as removed
-                    let entry = self.buckets[idx].take();
-                    self.buckets[idx] = Some(BucketEntry::Removed);
-                    if let Some(BucketEntry::Occupied(_, v)) = entry {
-                        return Some(v);
-                    }
-                }
-                Some(_) => {}
-            }
-            idx = (idx + 1) % self.buckets.len();
-            if idx == start_idx {
-                break;
+/// Custom HashMap using open addressing with linear probing and the assembly hash
+///
+/// Slot states live in a separate control-byte array holding a 7-bit hash
+/// fragment per full slot. Probes scan that array a group of 16 bytes at a
+/// time (with SSE2 on x86_64) and only compare keys whose fragment matches.
+///
+/// Keys are hashed by `S`, the assembly hash unless a [`KeyHasher`] is given
+/// through [`with_hasher`](Self::with_hasher).
+///
+/// With [`ResizeStrategy::Incremental`] a grown table is filled a few slots
+/// per operation, and until then the previous table is kept and searched too.
+pub struct CustomHashMap<K, V, S = AssemblyBuildHash> {
+    /// One control byte per slot, followed by a copy of the first
+    /// `GROUP_WIDTH` bytes so a group load near the end sees the wrapped slots
+    ctrl: Vec<u8>,
+    /// `slots[i]` holds a bucket exactly when `ctrl[i]` is full
+    slots: Slots<K, V>,
+    len: usize,
+    tombstones: usize,
+    /// Cached `policy.grow_at` and `policy.compact_at` for the current size
+    grow_at: usize,
+    compact_at: usize,
+    policy: Policy,
+    hasher: S,
+    /// Table still being drained by an incremental resize
+    old: Option<OldTable<K, V>>,
+}
+
+/// The pre-resize table during an incremental resize. Nothing is inserted
+/// into it; entries leave by migration or removal, each leaving a tombstone
+/// so probes for the remaining ones still pass.
+struct OldTable<K, V> {
+    ctrl: Vec<u8>,
+    slots: Slots<K, V>,
+    /// Slots below this index have been moved to the new table
+    migrated: usize,
+    /// Last empty slot below `migrated`
+    last_empty: Option<usize>,
+    /// Slots moved per operation
+    batch: usize,
+}
+
+impl<K, V> OldTable<K, V> {
+    /// Returns the slot holding a key with `hash` for which `eq` holds.
+    /// Migration leaves only tombstones and empty slots below `migrated`, so
+    /// a probe from a home there can't find the key before `migrated` and
+    /// resumes at it, unless an empty slot in between shows the key was
+    /// never past it.
+    fn find(&self, probe: ProbeStrategy, hash: u32, eq: impl FnMut(&K) -> bool) -> Option<usize> {
+        let home = hash as usize & (self.slots.len() - 1);
+        let mut skip = 0;
+        if home < self.migrated {
+            if self.last_empty.is_some_and(|empty| empty >= home) {
+                return None;
             }
+            skip = self.migrated - home;
         }
+        probe_in(&self.ctrl, &self.slots, probe, hash, skip, eq).ok()
+    }
 
-        None
+    fn bucket(&self, idx: usize) -> &Bucket<K, V> {
+        debug_assert!(is_full(self.ctrl[idx]));
+        // SAFETY: callers pass a slot a probe found full
+        unsafe { self.slots[idx].assume_init_ref() }
+    }
+
+    fn take(&mut self, idx: usize) -> Option<Bucket<K, V>> {
+        if !is_full(self.ctrl[idx]) {
+            return None;
+        }
+        write_ctrl(&mut self.ctrl, idx, DELETED);
+        // SAFETY: the slot was full, and the tombstone hands its bucket over
+        Some(unsafe { self.slots[idx].assume_init_read() })
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for OldTable<K, V> {
+    fn clone(&self) -> Self {
+        let cap = self.slots.len();
+        let mut new = Self {
+            ctrl: vec![EMPTY; cap + GROUP_WIDTH],
+            slots: uninit_slots(cap),
+            migrated: self.migrated,
+            last_empty: self.last_empty,
+            batch: self.batch,
+        };
+        clone_slots_into(&mut new.ctrl, &mut new.slots, &self.ctrl, &self.slots);
+        new
+    }
+}
+
+impl<K, V> Drop for OldTable<K, V> {
+    fn drop(&mut self) {
+        drop_slots(&self.ctrl, &mut self.slots);
     }
+}
+
+impl<K, V> CustomHashMap<K, V> {
+    pub fn new() -> Self {
+        CustomHashMapBuilder::new().build()
+    }
+
+    /// Creates a map that holds `capacity` entries before it first grows
+    pub fn with_capacity(capacity: usize) -> Self {
+        CustomHashMapBuilder::new()
+            .initial_capacity(capacity)
+            .build()
+    }
+
+    pub fn with_deletion_strategy(deletion: DeletionStrategy) -> Self {
+        CustomHashMapBuilder::new()
+            .deletion_strategy(deletion)
+            .build()
+    }
+
+    pub fn with_capacity_and_deletion_strategy(
+        capacity: usize,
+        deletion: DeletionStrategy,
+    ) -> Self {
+        CustomHashMapBuilder::new()
+            .initial_capacity(capacity)
+            .deletion_strategy(deletion)
+            .build()
+    }
+
+    pub fn with_probe_strategy(probe: ProbeStrategy) -> Self {
+        CustomHashMapBuilder::new().probe_strategy(probe).build()
+    }
+
+    pub fn with_capacity_and_probe_strategy(capacity: usize, probe: ProbeStrategy) -> Self {
+        CustomHashMapBuilder::new()
+            .initial_capacity(capacity)
+            .probe_strategy(probe)
+            .build()
+    }
+}
 
-    pub fn contains_key(&self, key: &K) -> bool {
-        self.get(key).is_some()
+impl<K, V, S> CustomHashMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        CustomHashMapBuilder::new().build_with_hasher(hasher)
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        CustomHashMapBuilder::new()
+            .initial_capacity(capacity)
+            .build_with_hasher(hasher)
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hasher
     }
 
     pub fn len(&self) -> usize {
         self.len
     }
 
+    pub fn deletion_strategy(&self) -> DeletionStrategy {
+        self.policy.deletion
+    }
+
+    pub fn probe_strategy(&self) -> ProbeStrategy {
+        self.policy.probe
+    }
+
+    pub fn max_load_factor(&self) -> f64 {
+        self.policy.max_load_factor
+    }
+
+    pub fn tombstone_threshold(&self) -> f64 {
+        self.policy.tombstone_threshold
+    }
+
+    pub fn growth_multiplier(&self) -> usize {
+        self.policy.growth_multiplier
+    }
+
+    pub fn resize_strategy(&self) -> ResizeStrategy {
+        self.policy.resize
+    }
+
+    /// Whether an incremental resize is still moving entries over
+    pub fn is_resizing(&self) -> bool {
+        self.old.is_some()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
 
-    fn ensure_capacity(&mut self) {
-        // Resize at 75% load factor
-        if self.len * 4 >= self.buckets.len() * 3 {
-            self.resize(self.buckets.len() * 2);
-        }
+    /// Number of entries the map holds before its next growth. Tombstones
+    /// left by removals also use up this headroom until they are compacted.
+    pub fn capacity(&self) -> usize {
+        self.grow_at
     }
 
-    fn resize(&mut self, new_capacity: usize) {
-        let mut new_buckets = Vec::with_capacity(new_capacity);
-        for _ in 0..new_capacity {
-            new_buckets.push(None);
+    /// Creates an empty table of `cap` slots, a power of two
+    fn with_policy(cap: usize, mut policy: Policy, hasher: S) -> Self {
+        if policy.probe == ProbeStrategy::RobinHood {
+            policy.deletion = DeletionStrategy::BackwardShift;
         }
-        let old_buckets = std::mem::replace(&mut self.buckets, new_buckets);
-        self.len = 0;
-
-        for entry in old_buckets {
-            if let Some(BucketEntry::Occupied(key, value)) = entry {
-                self.insert(key, value);
-            }
+        Self {
+            ctrl: vec![EMPTY; cap + GROUP_WIDTH],
+            slots: uninit_slots(cap),
+            len: 0,
+            tombstones: 0,
+            grow_at: policy.grow_at(cap),
+            compact_at: policy.compact_at(cap),
+            policy,
+            hasher,
+            old: None,
         }
     }
 }
 
-impl<K, V> Default for CustomHashMap<K, V>
+impl<K, V, S> CustomHashMap<K, V, S>
 where
-    K: Eq + AssemblyHash + Clone,
+    K: Eq,
+    S: KeyHasher<K>,
 {
-    fn default() -> Self {
-        Self::new()
+    fn lookup_key<Q>(&self, key: &Q) -> Option<&Bucket<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.lookup(self.hasher.hash_key(key), |k| k.borrow() == key)
     }
-}
 
-impl<K, V> Clone for CustomHashMap<K, V>
-where
-    K: Eq + AssemblyHash + Clone,
-    V: Clone,
-{
-    fn clone(&self) -> Self {
-        let mut new = Self::with_capacity(self.buckets.len());
-        for entry in &self.buckets {
-            if let Some(BucketEntry::Occupied(key, value)) = entry {
-                new.insert(key.clone(), value.clone());
+    /// Returns the slot holding `key`, moving it out of the old table first
+    /// if a resize is in progress
+    fn find_key_mut<Q>(&mut self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.find_hashed_mut(self.hasher.hash_key(key), |k| k.borrow() == key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hasher.hash_key(&key);
+        self.insert_with_hash(hash, key, value)
+    }
+
+    /// Gets the entry for `key` for in-place manipulation. The slot is located
+    /// once, so updating or inserting through the entry costs a single probe.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        self.ensure_capacity();
+
+        let hash = self.hasher.hash_key(&key);
+        match self.find_slot(hash, &key) {
+            Ok(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            Err(index) => Entry::Vacant(VacantEntry {
+                map: self,
+                key,
+                hash,
+                index,
+            }),
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        Some(&self.lookup_key(key)?.value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let idx = self.find_key_mut(key)?;
+        Some(&mut self.bucket_mut(idx).value)
+    }
+
+    /// Returns mutable references to the values of `N` keys at once, or
+    /// `None` if any key is missing or the same key appears twice
+    pub fn get_many_mut<Q, const N: usize>(&mut self, keys: [&Q; N]) -> Option<[&mut V; N]>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        if self.old.is_some() {
+            // Move every key over before taking indices, since under Robin
+            // Hood a later move can displace an entry already located
+            for key in keys {
+                self.find_key_mut(key)?;
             }
         }
-        new
+        let mut indices = [0; N];
+        for (idx, key) in indices.iter_mut().zip(keys) {
+            *idx = self.find_key_mut(key)?;
+        }
+        // Equal keys resolve to the same slot, which get_disjoint_mut rejects
+        let slots = self.slots.get_disjoint_mut(indices).ok()?;
+        // SAFETY: every index came from a lookup, so its slot is full
+        Some(slots.map(|slot| &mut unsafe { slot.assume_init_mut() }.value))
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let hash = self.hasher.hash_key(key);
+        Some(self.remove_hashed(hash, |k| k.borrow() == key)?.1)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.lookup_key(key).is_some()
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let bucket = self.lookup_key(key)?;
+        Some((&bucket.key, &bucket.value))
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let hash = self.hasher.hash_key(key);
+        self.remove_hashed(hash, |k| k.borrow() == key)
+    }
+
+    /// Inserts the pair only if `key` is absent. Otherwise the map is left
+    /// untouched and the error carries the existing entry and `value`.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<&mut V, OccupiedError<'_, K, V, S>> {
+        match self.entry(key) {
+            Entry::Occupied(entry) => Err(OccupiedError { entry, value }),
+            Entry::Vacant(entry) => Ok(entry.insert(value)),
+        }
+    }
+}
+
+impl<K: Eq, V, S> CustomHashMap<K, V, S> {
+    /// Returns the slot holding `key`, or else the slot a new entry with this
+    /// hash should go to. Under linear probing that is the first tombstone or
+    /// empty slot the lookup passed, so both come out of a single scan.
+    fn find_slot(&mut self, hash: u32, key: &K) -> Result<usize, usize> {
+        let free = match self.probe(hash, |k| k == key) {
+            Ok(idx) => return Ok(idx),
+            Err(free) => free,
+        };
+        if let Some(idx) = self.pull_old(hash, |k| k == key) {
+            return Ok(idx);
+        }
+        match free {
+            Some(free) if self.policy.probe == ProbeStrategy::Linear => Err(free),
+            _ => Err(self.find_insert_slot(hash)),
+        }
+    }
+
+    /// Raw insert with a hash the caller already has, such as the result of
+    /// [`assembly_hash_bytes`] on a lexer token for the default hasher. `hash`
+    /// must equal `self.hasher().hash_key(&key)`, or key-based lookups will
+    /// miss the entry.
+    pub fn insert_with_hash(&mut self, hash: u32, key: K, value: V) -> Option<V> {
+        self.ensure_capacity();
+
+        match self.find_slot(hash, &key) {
+            // If key exists, update value
+            Ok(idx) => Some(std::mem::replace(&mut self.bucket_mut(idx).value, value)),
+            Err(slot) => {
+                self.occupy(slot, hash, key, value);
+                None
+            }
+        }
+    }
+
+    /// Grows the table so `additional` more entries fit without reallocating
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new slot count overflows `usize`.
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(err) = self.try_reserve(additional) {
+            panic!("{err}");
+        }
+    }
+
+    /// Like [`reserve`](Self::reserve), but reports overflow or a failed
+    /// allocation instead of panicking
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        // Inserts also rehash once tombstones pass the compaction threshold
+        let compacts = self.tombstones >= self.compact_at;
+        if needed.saturating_add(self.tombstones) <= self.grow_at && !compacts {
+            return Ok(());
+        }
+        let slots = self
+            .policy
+            .slots_for(needed)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        // A compaction may find the entries fit a smaller table, but reserving
+        // never shrinks; that is up to `shrink_to`
+        self.try_resize(slots.max(self.slots.len()))
+    }
+
+    /// Shrinks the table as far as the current entries allow
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the table so it still holds at least `min_capacity` entries.
+    /// Tombstones are dropped even if the slot count stays the same.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let Some(target) = self.policy.slots_for(self.len.max(min_capacity)) else {
+            return;
+        };
+        if target < self.slots.len() || (target == self.slots.len() && self.tombstones > 0) {
+            self.resize(target);
+        }
+    }
+
+    fn ensure_capacity(&mut self) {
+        self.migrate_step();
+
+        // Rehash at the current size once tombstones pass the threshold, and
+        // grow at the max load factor. Tombstones count towards the load since
+        // probes walk over them, unless a compaction is about to drop them.
+        let compacts = self.tombstones >= self.compact_at;
+        let load = if compacts {
+            self.len
+        } else {
+            self.len + self.tombstones
+        };
+        let new_capacity = if load >= self.grow_at {
+            self.slots
+                .len()
+                .checked_mul(self.policy.growth_multiplier)
+                .expect("capacity overflow")
+        } else if compacts {
+            self.slots.len()
+        } else {
+            return;
+        };
+
+        match self.policy.resize {
+            ResizeStrategy::Immediate => self.resize(new_capacity),
+            ResizeStrategy::Incremental { min_batch } => {
+                self.start_resize(new_capacity, min_batch);
+                self.migrate_step();
+            }
+        }
+    }
+
+    /// Rebuilds the table with `new_capacity` slots, dropping all tombstones
+    fn resize(&mut self, new_capacity: usize) {
+        self.finish_resize();
+        self.rehash_into(
+            vec![EMPTY; new_capacity + GROUP_WIDTH],
+            uninit_slots(new_capacity),
+        );
+    }
+
+    /// Fallible [`resize`](Self::resize); the table is untouched on error
+    fn try_resize(&mut self, new_capacity: usize) -> Result<(), TryReserveError> {
+        let slot_bytes = new_capacity.checked_mul(size_of::<Bucket<K, V>>());
+        if slot_bytes.is_none_or(|bytes| bytes > isize::MAX as usize) {
+            return Err(TryReserveError::CapacityOverflow);
+        }
+
+        self.finish_resize();
+        let mut ctrl = Vec::new();
+        ctrl.try_reserve_exact(new_capacity + GROUP_WIDTH)?;
+        ctrl.resize(new_capacity + GROUP_WIDTH, EMPTY);
+        let mut slots = Vec::new();
+        slots.try_reserve_exact(new_capacity)?;
+        slots.resize_with(new_capacity, MaybeUninit::uninit);
+
+        self.rehash_into(ctrl, slots);
+        Ok(())
+    }
+
+    fn rehash_into(&mut self, ctrl: Vec<u8>, slots: Slots<K, V>) {
+        let old_ctrl = std::mem::replace(&mut self.ctrl, ctrl);
+        let old_slots = std::mem::replace(&mut self.slots, slots);
+        self.tombstones = 0;
+        self.grow_at = self.policy.grow_at(self.slots.len());
+        self.compact_at = self.policy.compact_at(self.slots.len());
+        self.len = 0;
+
+        for (&byte, slot) in old_ctrl.iter().zip(&old_slots) {
+            if !is_full(byte) {
+                continue;
+            }
+            // SAFETY: the slot is full and read only once; the old storage
+            // is then freed without dropping anything
+            let bucket = unsafe { slot.assume_init_read() };
+            // Keys are already unique, so only the insertion point is needed
+            let slot = self.find_insert_slot(bucket.hash);
+            self.occupy(slot, bucket.hash, bucket.key, bucket.value);
+        }
+    }
+}
+
+/// Sets the control byte of `idx`, keeping the trailing mirror in sync
+fn write_ctrl(ctrl: &mut [u8], idx: usize, byte: u8) {
+    let cap = ctrl.len() - GROUP_WIDTH;
+    let mut pos = idx;
+    while pos < ctrl.len() {
+        ctrl[pos] = byte;
+        pos += cap;
+    }
+}
+
+/// Group-wise probe for a key with `hash` matching `eq`, starting `skip`
+/// slots past its home. Only slots whose control byte carries the same 7-bit
+/// fragment reach `eq`, and the probe ends at the first empty slot since a
+/// key never sits past one. On a miss, returns the first tombstone or empty
+/// slot passed, if any.
+fn probe_in<K, V>(
+    ctrl: &[u8],
+    slots: &[MaybeUninit<Bucket<K, V>>],
+    probe: ProbeStrategy,
+    hash: u32,
+    skip: usize,
+    mut eq: impl FnMut(&K) -> bool,
+) -> Result<usize, Option<usize>> {
+    let cap = slots.len();
+    let fragment = h2(hash);
+    let mut pos = (hash as usize).wrapping_add(skip) & (cap - 1);
+    let mut probed = skip;
+    let mut first_free = None;
+
+    while probed < cap {
+        let group = Group::load(&ctrl[pos..]);
+        let empty = group.match_empty().lowest();
+        let mut candidates = group.match_byte(fragment);
+        if let Some(bit) = empty {
+            candidates = candidates.below(bit);
+        }
+
+        for bit in candidates {
+            let idx = (pos + bit) & (cap - 1);
+            // SAFETY: the control byte matched a hash fragment, so the slot is full
+            let bucket = unsafe { slots[idx].assume_init_ref() };
+            if bucket.hash == hash && eq(&bucket.key) {
+                return Ok(idx);
+            }
+        }
+
+        if first_free.is_none() {
+            first_free = group
+                .match_empty_or_deleted()
+                .lowest()
+                .map(|bit| (pos + bit) & (cap - 1));
+        }
+        if empty.is_some() {
+            return Err(first_free);
+        }
+
+        // Robin Hood: passing an entry closer to home than we are means
+        // the key would have displaced it, so it can't be further along
+        let last = (pos + GROUP_WIDTH - 1) & (cap - 1);
+        if is_full(ctrl[last]) {
+            // SAFETY: the slot is full
+            let bucket = unsafe { slots[last].assume_init_ref() };
+            if probe.stops_probe(bucket.dist(last, cap), probed + GROUP_WIDTH - 1) {
+                return Err(first_free);
+            }
+        }
+
+        pos = (pos + GROUP_WIDTH) & (cap - 1);
+        probed += GROUP_WIDTH;
+    }
+
+    Err(first_free)
+}
+
+/// Bucket storage; which slots are initialized is recorded only by the
+/// control bytes alongside
+type Slots<K, V> = Vec<MaybeUninit<Bucket<K, V>>>;
+
+/// Storage for `cap` buckets, left uninitialized so nothing is written
+fn uninit_slots<K, V>(cap: usize) -> Slots<K, V> {
+    let mut slots = Vec::with_capacity(cap);
+    slots.resize_with(cap, MaybeUninit::uninit);
+    slots
+}
+
+/// Drops the bucket of every full slot
+fn drop_slots<K, V>(ctrl: &[u8], slots: &mut [MaybeUninit<Bucket<K, V>>]) {
+    if !std::mem::needs_drop::<Bucket<K, V>>() {
+        return;
+    }
+    for (&byte, slot) in ctrl.iter().zip(slots) {
+        if is_full(byte) {
+            // SAFETY: full slots are initialized, and each is dropped once
+            unsafe { slot.assume_init_drop() };
+        }
+    }
+}
+
+/// Turns `ctrl` and `slots` into a copy of `src_ctrl` and `src_slots`, a
+/// table of the same size. Buckets full on both sides are cloned in place.
+/// Each control byte is copied right after its slot, so if a clone panics
+/// the control bytes still mark exactly the initialized slots.
+fn clone_slots_into<K: Clone, V: Clone>(
+    ctrl: &mut [u8],
+    slots: &mut [MaybeUninit<Bucket<K, V>>],
+    src_ctrl: &[u8],
+    src_slots: &[MaybeUninit<Bucket<K, V>>],
+) {
+    for (idx, slot) in slots.iter_mut().enumerate() {
+        // SAFETY: full control bytes mark the initialized slots on both sides
+        unsafe {
+            match (is_full(ctrl[idx]), is_full(src_ctrl[idx])) {
+                (true, true) => slot
+                    .assume_init_mut()
+                    .clone_from(src_slots[idx].assume_init_ref()),
+                (true, false) => {
+                    write_ctrl(ctrl, idx, src_ctrl[idx]);
+                    slot.assume_init_drop();
+                }
+                (false, true) => {
+                    slot.write(src_slots[idx].assume_init_ref().clone());
+                }
+                (false, false) => {}
+            }
+        }
+        write_ctrl(ctrl, idx, src_ctrl[idx]);
+    }
+}
+
+impl<K, V, S> CustomHashMap<K, V, S> {
+    /// Capacity is always a power of two, so the home slot is a mask away
+    fn home(&self, hash: u32) -> usize {
+        hash as usize & (self.slots.len() - 1)
+    }
+
+    /// The bucket in `idx`, which must be a full slot
+    fn bucket(&self, idx: usize) -> &Bucket<K, V> {
+        debug_assert!(is_full(self.ctrl[idx]));
+        // SAFETY: callers pass slots found full through their control bytes
+        unsafe { self.slots[idx].assume_init_ref() }
+    }
+
+    fn bucket_mut(&mut self, idx: usize) -> &mut Bucket<K, V> {
+        debug_assert!(is_full(self.ctrl[idx]));
+        // SAFETY: as in `bucket`
+        unsafe { self.slots[idx].assume_init_mut() }
+    }
+
+    fn set_ctrl(&mut self, idx: usize, byte: u8) {
+        write_ctrl(&mut self.ctrl, idx, byte);
+    }
+
+    /// Returns the slot holding a key with `hash` for which `eq` holds
+    fn find_hashed(&self, hash: u32, eq: impl FnMut(&K) -> bool) -> Option<usize> {
+        self.probe(hash, eq).ok()
+    }
+
+    /// Like [`find_hashed`](Self::find_hashed), but also searches the old
+    /// table of an incremental resize
+    fn lookup(&self, hash: u32, mut eq: impl FnMut(&K) -> bool) -> Option<&Bucket<K, V>> {
+        if let Some(idx) = self.find_hashed(hash, &mut eq) {
+            return Some(self.bucket(idx));
+        }
+        let old = self.old.as_ref()?;
+        let idx = old.find(self.policy.probe, hash, eq)?;
+        Some(old.bucket(idx))
+    }
+
+    /// Like [`find_hashed`](Self::find_hashed), but a match still in the old
+    /// table is moved over first, so the index is always into `slots`
+    fn find_hashed_mut(&mut self, hash: u32, mut eq: impl FnMut(&K) -> bool) -> Option<usize> {
+        if let Some(idx) = self.find_hashed(hash, &mut eq) {
+            return Some(idx);
+        }
+        self.pull_old(hash, eq)
+    }
+
+    /// Moves the old-table entry with `hash` matching `eq` into the new table
+    fn pull_old(&mut self, hash: u32, eq: impl FnMut(&K) -> bool) -> Option<usize> {
+        let probe = self.policy.probe;
+        let old = self.old.as_mut()?;
+        let idx = old.find(probe, hash, eq)?;
+        let bucket = old.take(idx)?;
+        Some(self.migrate_bucket(bucket))
+    }
+
+    /// Removes the entry with `hash` matching `eq` from whichever table holds
+    /// it. The old table only ever gets tombstones, as nothing probes into it
+    /// for inserts.
+    fn remove_hashed(&mut self, hash: u32, mut eq: impl FnMut(&K) -> bool) -> Option<(K, V)> {
+        self.migrate_step();
+        if let Some(idx) = self.find_hashed(hash, &mut eq) {
+            return Some(self.erase(idx));
+        }
+        let probe = self.policy.probe;
+        let old = self.old.as_mut()?;
+        let idx = old.find(probe, hash, eq)?;
+        let bucket = old.take(idx)?;
+        self.len -= 1;
+        Some((bucket.key, bucket.value))
+    }
+
+    /// Swaps in an empty table of `new_capacity` slots. The current one is
+    /// kept and drained by later operations, at least `min_batch` slots each.
+    fn start_resize(&mut self, new_capacity: usize, min_batch: usize) {
+        self.finish_resize();
+        let old_capacity = self.slots.len();
+        let ctrl = std::mem::replace(&mut self.ctrl, vec![EMPTY; new_capacity + GROUP_WIDTH]);
+        let slots = std::mem::replace(&mut self.slots, uninit_slots(new_capacity));
+        self.tombstones = 0;
+        self.grow_at = self.policy.grow_at(new_capacity);
+        self.compact_at = self.policy.compact_at(new_capacity);
+
+        // Every operation that can add an entry or tombstone moves a batch
+        // first, so the old table is empty by the time the map holds as many
+        // entries as it has slots. Past that, new keys would land on homes
+        // inside its full clusters and every insert would search them, and
+        // the next growth would have to finish the move in one go.
+        let headroom = old_capacity.min(self.grow_at).saturating_sub(self.len);
+        self.old = Some(OldTable {
+            ctrl,
+            slots,
+            migrated: 0,
+            last_empty: None,
+            batch: min_batch.max(old_capacity.div_ceil(headroom.max(1))),
+        });
+    }
+
+    /// Moves one batch of old-table slots over under incremental resizing
+    fn migrate_step(&mut self) {
+        if let Some(old) = &self.old {
+            self.migrate(old.batch);
+        }
+    }
+
+    fn finish_resize(&mut self) {
+        self.migrate(usize::MAX);
+    }
+
+    /// Moves the next `budget` old-table slots over, dropping the old table
+    /// once it is drained
+    fn migrate(&mut self, budget: usize) {
+        let Some(old) = &self.old else {
+            return;
+        };
+        let start = old.migrated;
+        let end = start.saturating_add(budget).min(old.slots.len());
+
+        for idx in start..end {
+            let old = self
+                .old
+                .as_mut()
+                .expect("old table present while migrating");
+            if old.ctrl[idx] == EMPTY {
+                old.last_empty = Some(idx);
+            } else if let Some(bucket) = old.take(idx) {
+                self.migrate_bucket(bucket);
+            }
+        }
+
+        let old = self
+            .old
+            .as_mut()
+            .expect("old table present while migrating");
+        old.migrated = end;
+        if end == old.slots.len() {
+            self.old = None;
+        }
+    }
+
+    /// Places a bucket taken from the old table, reusing its cached hash. It
+    /// is already counted in `len`.
+    fn migrate_bucket(&mut self, bucket: Bucket<K, V>) -> usize {
+        let slot = self.find_insert_slot(bucket.hash);
+        self.occupy(slot, bucket.hash, bucket.key, bucket.value);
+        self.len -= 1;
+        slot
+    }
+
+    fn probe(&self, hash: u32, eq: impl FnMut(&K) -> bool) -> Result<usize, Option<usize>> {
+        probe_in(&self.ctrl, &self.slots, self.policy.probe, hash, 0, eq)
+    }
+
+    /// Finds where a key known to be absent goes: the first tombstone or empty
+    /// slot from home, or under Robin Hood the first slot holding an entry
+    /// closer to its own home.
+    fn find_insert_slot(&self, hash: u32) -> usize {
+        let cap = self.slots.len();
+        let mut pos = self.home(hash);
+
+        if self.policy.probe == ProbeStrategy::RobinHood {
+            let mut dist = 0;
+            while is_full(self.ctrl[pos]) {
+                let slot_dist = self.bucket(pos).dist(pos, cap);
+                if self.policy.probe.stops_probe(slot_dist, dist) {
+                    break;
+                }
+                pos = (pos + 1) & (cap - 1);
+                dist += 1;
+            }
+            return pos;
+        }
+
+        // ensure_capacity keeps free slots around, so this ends well before wrapping
+        loop {
+            let group = Group::load(&self.ctrl[pos..]);
+            if let Some(bit) = group.match_empty_or_deleted().lowest() {
+                return (pos + bit) & (cap - 1);
+            }
+            pos = (pos + GROUP_WIDTH) & (cap - 1);
+        }
+    }
+
+    /// Writes a new entry into `slot`, as located by `find_insert_slot`. A
+    /// tombstone is reclaimed; under Robin Hood an occupant is displaced further along.
+    fn occupy(&mut self, slot: usize, hash: u32, key: K, value: V) {
+        let old_ctrl = self.ctrl[slot];
+        self.len += 1;
+
+        if old_ctrl == DELETED {
+            self.tombstones -= 1;
+        }
+        self.set_ctrl(slot, h2(hash));
+        let bucket = Bucket { key, value, hash };
+        if is_full(old_ctrl) {
+            // SAFETY: the slot was full; its occupant moves further along
+            let occupant = unsafe { self.slots[slot].assume_init_mut() };
+            let displaced = std::mem::replace(occupant, bucket);
+            self.push_down(slot, displaced, old_ctrl);
+        } else {
+            self.slots[slot].write(bucket);
+        }
+    }
+
+    /// Robin Hood displacement: carries `bucket` forward from `idx`, swapping
+    /// it with any occupant closer to home, until an empty slot takes it
+    fn push_down(&mut self, mut idx: usize, mut bucket: Bucket<K, V>, mut ctrl: u8) {
+        let cap = self.slots.len();
+        loop {
+            idx = (idx + 1) & (cap - 1);
+
+            if !is_full(self.ctrl[idx]) {
+                self.slots[idx].write(bucket);
+                self.set_ctrl(idx, ctrl);
+                return;
+            }
+            let occupant = self.bucket_mut(idx);
+            if occupant.dist(idx, cap) < bucket.dist(idx, cap) {
+                std::mem::swap(occupant, &mut bucket);
+                let occupant_ctrl = self.ctrl[idx];
+                self.set_ctrl(idx, ctrl);
+                ctrl = occupant_ctrl;
+            }
+        }
+    }
+
+    /// Removes the occupied entry at `idx` according to the deletion strategy
+    fn erase(&mut self, idx: usize) -> (K, V) {
+        debug_assert!(is_full(self.ctrl[idx]));
+        // SAFETY: the slot is full, and its control byte is cleared below,
+        // handing the bucket over
+        let bucket = unsafe { self.slots[idx].assume_init_read() };
+        self.len -= 1;
+
+        match self.policy.deletion {
+            DeletionStrategy::Tombstone => {
+                self.tombstones += 1;
+                self.set_ctrl(idx, DELETED);
+            }
+            DeletionStrategy::BackwardShift => {
+                self.set_ctrl(idx, EMPTY);
+                self.backward_shift(idx);
+            }
+        }
+
+        (bucket.key, bucket.value)
+    }
+
+    /// Closes the gap at `hole` by moving later cluster members back. An entry
+    /// moves only if `hole` lies between its home bucket and its current slot,
+    /// which keeps every key reachable from home without crossing an empty slot.
+    fn backward_shift(&mut self, mut hole: usize) {
+        let cap = self.slots.len();
+        let mut idx = (hole + 1) & (cap - 1);
+
+        while is_full(self.ctrl[idx]) {
+            let shift = (idx + cap - hole) & (cap - 1);
+            if self.bucket(idx).dist(idx, cap) >= shift {
+                // The hole is uninitialized, so a swap moves the bucket into it
+                self.slots.swap(hole, idx);
+                self.set_ctrl(hole, self.ctrl[idx]);
+                self.set_ctrl(idx, EMPTY);
+                hole = idx;
+            } else if self.policy.probe == ProbeStrategy::RobinHood {
+                // Robin Hood clusters are sorted by home, so nothing later can move
+                break;
+            }
+            idx = (idx + 1) & (cap - 1);
+        }
+    }
+
+    /// Raw lookup of the entry with `hash` for which `eq` holds. Only keys
+    /// whose control byte matches `hash` reach `eq`, so `eq` can compare
+    /// against any representation, with or without a `Borrow` impl.
+    pub fn find(&self, hash: u32, eq: impl FnMut(&K) -> bool) -> Option<(&K, &V)> {
+        let bucket = self.lookup(hash, eq)?;
+        Some((&bucket.key, &bucket.value))
+    }
+
+    /// Mutable form of [`find`](Self::find)
+    pub fn find_mut(&mut self, hash: u32, eq: impl FnMut(&K) -> bool) -> Option<(&K, &mut V)> {
+        let idx = self.find_hashed_mut(hash, eq)?;
+        let bucket = self.bucket_mut(idx);
+        Some((&bucket.key, &mut bucket.value))
+    }
+
+    /// Raw removal of the entry that [`find`](Self::find) would return
+    pub fn remove_by_hash(&mut self, hash: u32, eq: impl FnMut(&K) -> bool) -> Option<(K, V)> {
+        self.remove_hashed(hash, eq)
+    }
+
+    /// Iterates over all key-value pairs in bucket order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        // Mid-resize, entries not migrated yet are still in the old table
+        let old = self.old.as_ref().map_or((&[][..], &[][..]), |old| {
+            (&old.ctrl[old.migrated..], &old.slots[old.migrated..])
+        });
+        Iter {
+            slots: self
+                .ctrl
+                .iter()
+                .zip(&self.slots)
+                .chain(old.0.iter().zip(old.1)),
+            remaining: self.len,
+        }
+    }
+
+    /// Iterates over all key-value pairs with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.finish_resize();
+        IterMut {
+            slots: self.ctrl.iter().zip(&mut self.slots),
+            remaining: self.len,
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    /// Removes all entries, yielding them as owned pairs. The bucket array
+    /// keeps its capacity; any entries not consumed are dropped with the iterator.
+    ///
+    /// Each entry leaves the map as it is yielded, so if the iterator is
+    /// leaked the map still holds exactly the entries it didn't yield.
+    pub fn drain(&mut self) -> Drain<'_, K, V, S> {
+        self.finish_resize();
+        // Clearing downward from an empty slot means every freed slot is
+        // followed by an empty one, so no probe is cut short and nothing
+        // needs shifting or marking
+        let idx = self.ctrl[..self.slots.len()]
+            .iter()
+            .position(|&byte| byte == EMPTY)
+            .unwrap_or(0);
+        let remaining = self.slots.len();
+        Drain {
+            map: self,
+            idx,
+            remaining,
+        }
+    }
+
+    /// Removes all entries, keeping the allocated capacity
+    pub fn clear(&mut self) {
+        // Empties the control bytes even if dropping an entry panics, so the
+        // entries not reached yet are leaked rather than dropped twice
+        struct Reset<'a>(&'a mut [u8]);
+
+        impl Drop for Reset<'_> {
+            fn drop(&mut self) {
+                self.0.fill(EMPTY);
+            }
+        }
+
+        self.old = None;
+        self.len = 0;
+        self.tombstones = 0;
+        let ctrl = Reset(&mut self.ctrl);
+        drop_slots(ctrl.0, &mut self.slots);
+    }
+
+    /// Clones the map into a table of the same slot count without the
+    /// tombstones, finishing any incremental resize in the copy. Entries are
+    /// placed by their cached hashes, so the hasher is never called. Without
+    /// tombstones or a pending resize this is a plain [`clone`](Clone::clone).
+    pub fn clone_compacted(&self) -> Self
+    where
+        K: Clone,
+        V: Clone,
+        S: Clone,
+    {
+        if self.tombstones == 0 && self.old.is_none() {
+            return self.clone();
+        }
+        let mut new = Self::with_policy(self.slots.len(), self.policy, self.hasher.clone());
+        let mut entries = self.iter();
+        while let Some(bucket) = entries.next_bucket() {
+            let slot = new.find_insert_slot(bucket.hash);
+            new.occupy(slot, bucket.hash, bucket.key.clone(), bucket.value.clone());
+        }
+        new
+    }
+
+    /// Keeps only the entries for which `f` returns `true`, in one pass over
+    /// the table
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        self.extract_if(|k, v| !f(k, v)).for_each(drop);
+    }
+
+    /// Lazily removes and yields the entries for which `pred` returns `true`.
+    /// Entries not reached because the iterator was dropped early stay in the map.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, S, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.finish_resize();
+        // Start right after an empty slot. No cluster spans it, so backward
+        // shifts only pull entries from ahead of the scan, never wrap already
+        // visited ones around to its end.
+        let cap = self.slots.len();
+        let start = self.ctrl[..cap]
+            .iter()
+            .position(|&ctrl| ctrl == EMPTY)
+            .map_or(0, |empty| (empty + 1) & (cap - 1));
+        ExtractIf {
+            map: self,
+            start,
+            scanned: 0,
+            pred,
+        }
+    }
+}
+
+/// A view into a single slot of a [`CustomHashMap`], created by [`CustomHashMap::entry`]
+pub enum Entry<'a, K, V, S = AssemblyBuildHash> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+/// An entry whose key is present in the map
+pub struct OccupiedEntry<'a, K, V, S = AssemblyBuildHash> {
+    map: &'a mut CustomHashMap<K, V, S>,
+    index: usize,
+}
+
+/// An entry whose key is absent; holds the slot the key will be written to
+pub struct VacantEntry<'a, K, V, S = AssemblyBuildHash> {
+    map: &'a mut CustomHashMap<K, V, S>,
+    key: K,
+    hash: u32,
+    index: usize,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S> {
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(&entry.key);
+                entry.insert(value)
+            }
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Runs `f` on the value if the entry is occupied
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Sets the value of the entry, inserting it if vacant
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.insert(value);
+                entry
+            }
+            Entry::Vacant(entry) => entry.insert_entry(value),
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.map.bucket(self.index).key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.map.bucket(self.index).value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.bucket_mut(self.index).value
+    }
+
+    /// Converts the entry into a mutable reference bound to the map's lifetime
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.bucket_mut(self.index).value
+    }
+
+    /// Replaces the value, returning the old one
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.erase(self.index)
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Writes the value into the located slot and returns a reference to it
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.insert_entry(value).into_mut()
+    }
+
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S> {
+        self.map.occupy(self.index, self.hash, self.key, value);
+        OccupiedEntry {
+            map: self.map,
+            index: self.index,
+        }
+    }
+}
+
+/// Error returned by [`CustomHashMap::try_insert`] when the key is present
+pub struct OccupiedError<'a, K, V, S = AssemblyBuildHash> {
+    /// The entry already in the map
+    pub entry: OccupiedEntry<'a, K, V, S>,
+    /// The value that was not inserted
+    pub value: V,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for OccupiedError<'_, K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedError")
+            .field("key", self.entry.key())
+            .field("old_value", self.entry.get())
+            .field("new_value", &self.value)
+            .finish()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Display for OccupiedError<'_, K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to insert {:?}, key {:?} already exists with value {:?}",
+            self.value,
+            self.entry.key(),
+            self.entry.get(),
+        )
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> std::error::Error for OccupiedError<'_, K, V, S> {}
+
+/// Control bytes paired with their slots
+type SlotIter<'a, K, V> =
+    Zip<std::slice::Iter<'a, u8>, std::slice::Iter<'a, MaybeUninit<Bucket<K, V>>>>;
+
+/// Iterator over `(&K, &V)`, created by [`CustomHashMap::iter`]
+pub struct Iter<'a, K, V> {
+    slots: Chain<SlotIter<'a, K, V>, SlotIter<'a, K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn next_bucket(&mut self) -> Option<&'a Bucket<K, V>> {
+        let (_, slot) = self.slots.find(|(byte, _)| is_full(**byte))?;
+        self.remaining -= 1;
+        // SAFETY: the slot is full
+        Some(unsafe { slot.assume_init_ref() })
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.next_bucket()?;
+        Some((&bucket.key, &bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+/// Iterator over `(&K, &mut V)`, created by [`CustomHashMap::iter_mut`]
+pub struct IterMut<'a, K, V> {
+    slots: Zip<std::slice::Iter<'a, u8>, std::slice::IterMut<'a, MaybeUninit<Bucket<K, V>>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, slot) = self.slots.find(|(byte, _)| is_full(**byte))?;
+        self.remaining -= 1;
+        // SAFETY: the slot is full
+        let bucket = unsafe { slot.assume_init_mut() };
+        Some((&bucket.key, &mut bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> FusedIterator for IterMut<'_, K, V> {}
+
+/// Iterator over `&K`, created by [`CustomHashMap::keys`]
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+impl<K, V> FusedIterator for Keys<'_, K, V> {}
+
+/// Iterator over `&V`, created by [`CustomHashMap::values`]
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+
+/// Iterator over `&mut V`, created by [`CustomHashMap::values_mut`]
+pub struct ValuesMut<'a, K, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+impl<K, V> FusedIterator for ValuesMut<'_, K, V> {}
+
+/// Owning iterator over `(K, V)`, created by `CustomHashMap::into_iter`
+pub struct IntoIter<K, V> {
+    slots: Zip<std::vec::IntoIter<u8>, std::vec::IntoIter<MaybeUninit<Bucket<K, V>>>>,
+    remaining: usize,
+}
+
+impl<K, V> IntoIter<K, V> {
+    /// Takes ownership of the buckets in the full slots of a table
+    fn new(ctrl: Vec<u8>, slots: Slots<K, V>, len: usize) -> Self {
+        Self {
+            slots: ctrl.into_iter().zip(slots),
+            remaining: len,
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, slot) = self.slots.find(|(byte, _)| is_full(*byte))?;
+        self.remaining -= 1;
+        // SAFETY: the slot is full, and the iterator yields it only once
+        let bucket = unsafe { slot.assume_init() };
+        Some((bucket.key, bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
+impl<K, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+/// Draining iterator over `(K, V)`, created by [`CustomHashMap::drain`]
+pub struct Drain<'a, K, V, S> {
+    map: &'a mut CustomHashMap<K, V, S>,
+    /// Slot just above the next one to clear
+    idx: usize,
+    /// Slots not yet cleared
+    remaining: usize,
+}
+
+impl<K, V, S> Iterator for Drain<'_, K, V, S> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            self.remaining -= 1;
+            self.idx = self.idx.checked_sub(1).unwrap_or(self.map.slots.len() - 1);
+            let byte = self.map.ctrl[self.idx];
+            if byte == EMPTY {
+                continue;
+            }
+            self.map.set_ctrl(self.idx, EMPTY);
+            if byte == DELETED {
+                self.map.tombstones -= 1;
+                continue;
+            }
+            self.map.len -= 1;
+            // SAFETY: the slot was full, and clearing its control byte hands
+            // the bucket over
+            let bucket = unsafe { self.map.slots[self.idx].assume_init_read() };
+            return Some((bucket.key, bucket.value));
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.len, Some(self.map.len))
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for Drain<'_, K, V, S> {}
+impl<K, V, S> FusedIterator for Drain<'_, K, V, S> {}
+
+impl<K, V, S> Drop for Drain<'_, K, V, S> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+/// Iterator removing the entries that match a predicate, created by
+/// [`CustomHashMap::extract_if`]
+pub struct ExtractIf<'a, K, V, S, F> {
+    map: &'a mut CustomHashMap<K, V, S>,
+    start: usize,
+    scanned: usize,
+    pred: F,
+}
+
+impl<K, V, S, F> Iterator for ExtractIf<'_, K, V, S, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        let cap = self.map.slots.len();
+        while self.scanned < cap {
+            let idx = (self.start + self.scanned) & (cap - 1);
+            if is_full(self.map.ctrl[idx]) {
+                let bucket = self.map.bucket_mut(idx);
+                if (self.pred)(&bucket.key, &mut bucket.value) {
+                    // A backward shift may refill `idx`, so it is scanned again
+                    return Some(self.map.erase(idx));
+                }
+            }
+            self.scanned += 1;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.map.len))
+    }
+}
+
+impl<K, V, S, F> FusedIterator for ExtractIf<'_, K, V, S, F> where F: FnMut(&K, &mut V) -> bool {}
+
+impl<K, V, S> IntoIterator for CustomHashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.finish_resize();
+        let ctrl = std::mem::take(&mut self.ctrl);
+        let slots = std::mem::take(&mut self.slots);
+        IntoIter::new(ctrl, slots, self.len)
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a CustomHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut CustomHashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, S: Default> Default for CustomHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+/// Copies the table slot for slot, tombstones and any unfinished
+/// incremental resize included, so nothing is hashed or probed
+impl<K, V, S> Clone for CustomHashMap<K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        let cap = self.slots.len();
+        let mut new = Self {
+            ctrl: vec![EMPTY; cap + GROUP_WIDTH],
+            slots: uninit_slots(cap),
+            len: self.len,
+            tombstones: self.tombstones,
+            grow_at: self.grow_at,
+            compact_at: self.compact_at,
+            policy: self.policy,
+            hasher: self.hasher.clone(),
+            old: self.old.clone(),
+        };
+        clone_slots_into(&mut new.ctrl, &mut new.slots, &self.ctrl, &self.slots);
+        new
+    }
+
+    /// Reuses the bucket array, and the keys and values in slots that are
+    /// full in both maps, when `self` already has the slot count of `source`
+    fn clone_from(&mut self, source: &Self) {
+        if self.slots.len() != source.slots.len() {
+            *self = source.clone();
+            return;
+        }
+        clone_slots_into(&mut self.ctrl, &mut self.slots, &source.ctrl, &source.slots);
+        self.len = source.len;
+        self.tombstones = source.tombstones;
+        self.grow_at = source.grow_at;
+        self.compact_at = source.compact_at;
+        self.policy = source.policy;
+        self.hasher.clone_from(&source.hasher);
+        self.old.clone_from(&source.old);
+    }
+}
+
+impl<K, V, S> Drop for CustomHashMap<K, V, S> {
+    fn drop(&mut self) {
+        drop_slots(&self.ctrl, &mut self.slots);
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for CustomHashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> PartialEq for CustomHashMap<K, V, S>
+where
+    K: Eq,
+    V: PartialEq,
+    S: KeyHasher<K>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K, V, S> Eq for CustomHashMap<K, V, S>
+where
+    K: Eq,
+    V: Eq,
+    S: KeyHasher<K>,
+{
+}
+
+impl<K, Q, V, S> Index<&Q> for CustomHashMap<K, V, S>
+where
+    K: Eq + Borrow<Q>,
+    Q: Eq + ?Sized,
+    S: KeyHasher<K> + KeyHasher<Q>,
+{
+    type Output = V;
+
+    /// # Panics
+    ///
+    /// Panics if the key is not present.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in CustomHashMap")
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for CustomHashMap<K, V, S>
+where
+    K: Eq,
+    S: KeyHasher<K> + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::with_hasher(S::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for CustomHashMap<K, V, S>
+where
+    K: Eq,
+    S: KeyHasher<K>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        // Duplicate keys are likely when extending a non-empty map, so only
+        // reserve for half the hint then
+        let hint = iter.size_hint().0;
+        self.reserve(if self.is_empty() {
+            hint
+        } else {
+            hint.div_ceil(2)
+        });
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for CustomHashMap<K, V, S>
+where
+    K: Eq + Copy,
+    V: Copy,
+    S: KeyHasher<K>,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for CustomHashMap<K, V>
+where
+    K: Eq + AssemblyHash,
+{
+    fn from(pairs: [(K, V); N]) -> Self {
+        Self::from_iter(pairs)
     }
 }
@@ -0,0 +1,463 @@
+//! An insertion-ordered map on top of [`CustomHashMap`].
+//!
+//! Entries live in a dense `Vec` in insertion order, and the hash table holds
+//! only their positions there, each under the hash of its key. Lookups
+//! compare against the key in the `Vec`, so every key is stored once and
+//! never cloned. Iteration walks the `Vec`, so output built from the map is
+//! deterministic and follows the order the keys were first inserted.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::Index;
+
+use crate::hash::{AssemblyBuildHash, CustomHashMap, KeyHasher};
+
+/// Insertion-ordered hash map with positional access
+pub struct CustomIndexMap<K, V, S = AssemblyBuildHash> {
+    /// Positions in `entries`, each filed under the hash of its key. The unit
+    /// hasher can't hash anything, so the table is only reached through the
+    /// raw API with hashes from `hasher`.
+    indices: CustomHashMap<usize, (), ()>,
+    entries: Vec<(K, V)>,
+    hasher: S,
+}
+
+impl<K, V> CustomIndexMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(AssemblyBuildHash)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, AssemblyBuildHash)
+    }
+}
+
+impl<K, V, S> CustomIndexMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            indices: CustomHashMap::with_hasher(()),
+            entries: Vec::new(),
+            hasher,
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            indices: CustomHashMap::with_capacity_and_hasher(capacity, ()),
+            entries: Vec::with_capacity(capacity),
+            hasher,
+        }
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.indices.clear();
+        self.entries.clear();
+    }
+
+    /// Returns the entry at position `index` in insertion order
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|(k, v)| (k, v))
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.entries.get_mut(index).map(|(k, v)| (&*k, v))
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.entries.last().map(|(k, v)| (k, v))
+    }
+
+    /// Iterates over the entries in insertion order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            entries: self.entries.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            entries: self.entries.iter_mut(),
+        }
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator {
+        self.entries.iter_mut().map(|(_, v)| v)
+    }
+}
+
+impl<K, V, S> CustomIndexMap<K, V, S>
+where
+    K: Eq,
+    S: KeyHasher<K>,
+{
+    /// Inserts the pair, returning the old value if the key was present. An
+    /// existing key keeps its position.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Like [`insert`](Self::insert), also returning the entry's position
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        let hash = self.hasher.hash_key(&key);
+        if let Some(index) = self.find(hash, &key) {
+            let old = std::mem::replace(&mut self.entries[index].1, value);
+            return (index, Some(old));
+        }
+        let index = self.entries.len();
+        self.indices.insert_with_hash(hash, index, ());
+        self.entries.push((key, value));
+        (index, None)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let index = self.get_index_of(key)?;
+        Some(&self.entries[index].1)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let index = self.get_index_of(key)?;
+        Some(&mut self.entries[index].1)
+    }
+
+    /// Returns the position of `key` in insertion order
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.find(self.hasher.hash_key(key), key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.get_index_of(key).is_some()
+    }
+
+    /// Removes `key` by moving the last entry into its position. O(1), but
+    /// perturbs the order of the moved entry.
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let index = self.remove_position(key)?;
+        Some(self.swap_remove_slot(index).1)
+    }
+
+    /// Removes `key` by shifting all later entries down one position. Keeps
+    /// the order, but costs O(n).
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let index = self.remove_position(key)?;
+        Some(self.shift_remove_slot(index).1)
+    }
+
+    /// Removes the entry at `index`, see [`swap_remove`](Self::swap_remove)
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        self.unlink(index)?;
+        Some(self.swap_remove_slot(index))
+    }
+
+    /// Removes the entry at `index`, see [`shift_remove`](Self::shift_remove)
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        self.unlink(index)?;
+        Some(self.shift_remove_slot(index))
+    }
+
+    /// Removes and returns the most recently inserted entry
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        self.unlink(self.entries.len().checked_sub(1)?);
+        self.entries.pop()
+    }
+
+    /// Keeps only the entries for which `f` returns `true`, preserving order
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        self.entries.retain_mut(|(k, v)| f(k, v));
+        if self.entries.len() < self.indices.len() {
+            self.rebuild_indices();
+        }
+    }
+
+    /// Reorders the entries by `cmp`, which sees both keys and values
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> std::cmp::Ordering,
+    {
+        self.entries
+            .sort_by(|(k1, v1), (k2, v2)| cmp(k1, v1, k2, v2));
+        self.rebuild_indices();
+    }
+
+    /// Reorders the entries by key
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.sort_by(|k1, _, k2, _| k1.cmp(k2));
+    }
+
+    /// Position of the entry whose key has `hash` and equals `key`
+    fn find<Q>(&self, hash: u32, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let entries = &self.entries;
+        let (&index, _) = self
+            .indices
+            .find(hash, |&index| entries[index].0.borrow() == key)?;
+        Some(index)
+    }
+
+    /// Drops the position of `key` from the table, returning it
+    fn remove_position<Q>(&mut self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let hash = self.hasher.hash_key(key);
+        let entries = &self.entries;
+        let (index, ()) = self
+            .indices
+            .remove_by_hash(hash, |&index| entries[index].0.borrow() == key)?;
+        Some(index)
+    }
+
+    /// Drops position `index` from the table, if there is such an entry
+    fn unlink(&mut self, index: usize) -> Option<()> {
+        let hash = self.hasher.hash_key(&self.entries.get(index)?.0);
+        self.indices.remove_by_hash(hash, |&i| i == index)?;
+        Some(())
+    }
+
+    /// Records that the entry at `from` is moving to `to`
+    fn relink(&mut self, from: usize, to: usize) {
+        let hash = self.hasher.hash_key(&self.entries[from].0);
+        self.indices.remove_by_hash(hash, |&i| i == from);
+        self.indices.insert_with_hash(hash, to, ());
+    }
+
+    /// Takes the entry at `index` out of the `Vec` after its position has
+    /// left the table, fixing the position of the entry moved into its place
+    fn swap_remove_slot(&mut self, index: usize) -> (K, V) {
+        let last = self.entries.len() - 1;
+        if index < last {
+            self.relink(last, index);
+        }
+        self.entries.swap_remove(index)
+    }
+
+    fn shift_remove_slot(&mut self, index: usize) -> (K, V) {
+        for from in index + 1..self.entries.len() {
+            self.relink(from, from - 1);
+        }
+        self.entries.remove(index)
+    }
+
+    fn rebuild_indices(&mut self) {
+        self.indices.clear();
+        for (index, (key, _)) in self.entries.iter().enumerate() {
+            let hash = self.hasher.hash_key(key);
+            self.indices.insert_with_hash(hash, index, ());
+        }
+    }
+}
+
+/// Iterator over `(&K, &V)` in insertion order, created by [`CustomIndexMap::iter`]
+pub struct Iter<'a, K, V> {
+    entries: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.entries.next_back().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+/// Iterator over `(&K, &mut V)` in insertion order, created by
+/// [`CustomIndexMap::iter_mut`]
+pub struct IterMut<'a, K, V> {
+    entries: std::slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(k, v)| (&*k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.entries.next_back().map(|(k, v)| (&*k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> FusedIterator for IterMut<'_, K, V> {}
+
+impl<K, V, S> IntoIterator for CustomIndexMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a CustomIndexMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut CustomIndexMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, S> Index<usize> for CustomIndexMap<K, V, S> {
+    type Output = V;
+
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn index(&self, index: usize) -> &V {
+        &self.entries[index].1
+    }
+}
+
+impl<K, V, S: Default> Default for CustomIndexMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Clone, V: Clone, S: Clone> Clone for CustomIndexMap<K, V, S> {
+    fn clone(&self) -> Self {
+        Self {
+            indices: self.indices.clone(),
+            entries: self.entries.clone(),
+            hasher: self.hasher.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.indices.clone_from(&source.indices);
+        self.entries.clone_from(&source.entries);
+        self.hasher.clone_from(&source.hasher);
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for CustomIndexMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for CustomIndexMap<K, V, S>
+where
+    K: Eq,
+    S: KeyHasher<K> + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for CustomIndexMap<K, V, S>
+where
+    K: Eq,
+    S: KeyHasher<K>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.entries.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
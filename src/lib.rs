@@ -1,5 +1,21 @@
-mod hash;
-pub use hash::{AssemblyHash, CustomHashMap};
+pub mod codegen;
+mod group;
+pub mod hash;
+pub mod indexmap;
+pub mod multimap;
+pub mod perfect;
+pub mod set;
+pub use hash::{
+    AssemblyBuildHash, AssemblyHash, CustomHashMap, CustomHashMapBuilder, DeletionStrategy,
+    FullContentHash, KeyHasher, MultiplicativeHash, OccupiedError, ProbeStrategy, ResizeStrategy,
+    TryReserveError, assembly_hash_bytes,
+};
+pub use indexmap::CustomIndexMap;
+pub use multimap::CustomMultiMap;
+pub use perfect::{PerfectHashError, PerfectHashMap};
+pub use set::CustomHashSet;
+
+use std::borrow::Borrow;
 
 pub const CACHE_SIZE: usize = 128;
 
@@ -72,7 +88,11 @@ where
         self.head = Some(i);
     }
 
-    fn access(&mut self, key: &K) {
+    fn access<Q>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Eq + AssemblyHash + ?Sized,
+    {
         let i = *self.map.get(key).unwrap();
 
         if Some(i) == self.head {
@@ -120,7 +140,11 @@ where
         None
     }
 
-    pub fn get(&mut self, key: &K) -> Option<&V> {
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + AssemblyHash + ?Sized,
+    {
         if self.map.contains_key(key) {
             self.access(key);
         }
@@ -130,7 +154,11 @@ where
             .and_then(|&i| self.entries[i].val.as_ref())
     }
 
-    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + AssemblyHash + ?Sized,
+    {
         if self.map.contains_key(key) {
             self.access(key);
         }
@@ -140,14 +168,22 @@ where
             .and_then(|&i| self.entries[i].val.as_mut())
     }
 
-    pub fn remove(&mut self, key: &K) -> Option<V> {
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + AssemblyHash + ?Sized,
+    {
         self.map.remove(key).map(|i| {
             self.detach(i);
             self.entries[i].val.take().unwrap()
         })
     }
 
-    pub fn contains(&self, key: &K) -> bool {
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + AssemblyHash + ?Sized,
+    {
         self.map.contains_key(key)
     }
 
@@ -162,4 +198,65 @@ where
     pub fn is_empty(&self) -> bool {
         self.map.is_empty()
     }
+
+    /// Keeps only the entries for which `f` returns `true`. Entries are
+    /// visited from most to least recently used, and survivors keep their
+    /// order.
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        self.extract_if(|k, v| !f(k, v)).for_each(drop);
+    }
+
+    /// Lazily removes and yields the entries for which `pred` returns `true`,
+    /// walking from most to least recently used. Each removed entry is
+    /// unlinked and dropped from the index with a single probe.
+    pub fn extract_if<F>(&mut self, pred: F) -> LRUExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        LRUExtractIf {
+            next: self.head,
+            cache: self,
+            pred,
+        }
+    }
+}
+
+/// Iterator removing the entries that match a predicate, created by
+/// [`LRUCache::extract_if`]
+pub struct LRUExtractIf<'a, K, V, F> {
+    cache: &'a mut LRUCache<K, V>,
+    next: Option<usize>,
+    pred: F,
+}
+
+impl<K, V, F> Iterator for LRUExtractIf<'_, K, V, F>
+where
+    K: Eq + AssemblyHash + Clone,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        while let Some(i) = self.next {
+            let entry = &mut self.cache.entries[i];
+            self.next = entry.next;
+
+            let val = entry.val.as_mut().unwrap();
+            if (self.pred)(&entry.key, val) {
+                self.cache.detach(i);
+                let val = self.cache.entries[i].val.take().unwrap();
+                let (key, _) = self
+                    .cache
+                    .map
+                    .remove_entry(&self.cache.entries[i].key)
+                    .unwrap();
+                return Some((key, val));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.cache.len()))
+    }
 }
@@ -0,0 +1,274 @@
+//! A map holding several values per key on top of [`CustomHashMap`].
+//!
+//! Each key owns a `Vec` of its values in insertion order. A key is removed
+//! together with its last value, so the table never holds empty vectors.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FusedIterator;
+
+use crate::hash::{self, AssemblyBuildHash, CustomHashMap, KeyHasher};
+
+/// Multi-value map using the same table and hashers as [`CustomHashMap`]
+pub struct CustomMultiMap<K, V, S = AssemblyBuildHash> {
+    map: CustomHashMap<K, Vec<V>, S>,
+    /// Number of values across all keys
+    len: usize,
+}
+
+impl<K, V> CustomMultiMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(AssemblyBuildHash)
+    }
+
+    /// Creates a map with room for `capacity` distinct keys
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, AssemblyBuildHash)
+    }
+}
+
+impl<K, V, S> CustomMultiMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            map: CustomHashMap::with_hasher(hasher),
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            map: CustomHashMap::with_capacity_and_hasher(capacity, hasher),
+            len: 0,
+        }
+    }
+
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    /// Total number of values across all keys
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Number of distinct keys
+    pub fn keys_len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.len = 0;
+    }
+
+    /// Iterates over every `(key, value)` pair, keys in bucket order and
+    /// each key's values in insertion order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            groups: self.map.iter(),
+            current: None,
+            remaining: self.len,
+        }
+    }
+
+    /// Iterates over each key together with all of its values
+    pub fn iter_all(&self) -> hash::Iter<'_, K, Vec<V>> {
+        self.map.iter()
+    }
+
+    pub fn keys(&self) -> hash::Keys<'_, K, Vec<V>> {
+        self.map.keys()
+    }
+}
+
+impl<K, V, S> CustomMultiMap<K, V, S>
+where
+    K: Eq,
+    S: KeyHasher<K>,
+{
+    /// Appends `value` to the values of `key`
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.entry(key).or_default().push(value);
+        self.len += 1;
+    }
+
+    /// Returns all values of `key` in insertion order; empty if the key is absent
+    pub fn get_all<Q>(&self, key: &Q) -> &[V]
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.map.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the first value inserted for `key`
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.get_all(key).first()
+    }
+
+    /// Iterates over the values of `key` mutably
+    pub fn get_all_mut<Q>(&mut self, key: &Q) -> std::slice::IterMut<'_, V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.map
+            .get_mut(key)
+            .map(|values| values.iter_mut())
+            .unwrap_or_default()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Removes the first value of `key` equal to `value`, dropping the key
+    /// once it has no values left
+    pub fn remove_one<Q>(&mut self, key: &Q, value: &V) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+        V: PartialEq,
+    {
+        let values = self.map.get_mut(key)?;
+        let pos = values.iter().position(|v| v == value)?;
+        let removed = values.remove(pos);
+        if values.is_empty() {
+            self.map.remove(key);
+        }
+        self.len -= 1;
+        Some(removed)
+    }
+
+    /// Removes `key` and returns its values in insertion order; empty if the
+    /// key is absent
+    pub fn remove_all<Q>(&mut self, key: &Q) -> Vec<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let values = self.map.remove(key).unwrap_or_default();
+        self.len -= values.len();
+        values
+    }
+
+    /// Keeps only the values for which `f` returns `true`, dropping keys
+    /// left without values
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        let mut len = 0;
+        self.map.retain(|key, values| {
+            values.retain_mut(|value| f(key, value));
+            len += values.len();
+            !values.is_empty()
+        });
+        self.len = len;
+    }
+}
+
+/// Iterator over `(&K, &V)` pairs, created by [`CustomMultiMap::iter`]
+pub struct Iter<'a, K, V> {
+    groups: hash::Iter<'a, K, Vec<V>>,
+    current: Option<(&'a K, std::slice::Iter<'a, V>)>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, values)) = &mut self.current
+                && let Some(value) = values.next()
+            {
+                self.remaining -= 1;
+                return Some((*key, value));
+            }
+            let (key, values) = self.groups.next()?;
+            self.current = Some((key, values.iter()));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<'a, K, V, S> IntoIterator for &'a CustomMultiMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V, S: Default> Default for CustomMultiMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Clone, V: Clone, S: Clone> Clone for CustomMultiMap<K, V, S> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+            len: self.len,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.map.clone_from(&source.map);
+        self.len = source.len;
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for CustomMultiMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_all()).finish()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for CustomMultiMap<K, V, S>
+where
+    K: Eq,
+    S: KeyHasher<K> + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for CustomMultiMap<K, V, S>
+where
+    K: Eq,
+    S: KeyHasher<K>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
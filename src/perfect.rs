@@ -0,0 +1,306 @@
+//! A read-only map over a fixed key set with collision-free slots.
+//!
+//! Building searches for a multiplier seed and power-of-two table size under
+//! which every key's hash lands in its own slot. A lookup then costs one hash,
+//! one multiply and a single key comparison, with no probing, as in strager's
+//! perfect hash table talk. Keys whose hashes are equal can't be separated by
+//! any seed; with the default hash that means keys sharing their first and
+//! last two bytes, or any two keys shorter than 4 bytes, and
+//! [`FullContentHash`](crate::FullContentHash) is the way out.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FusedIterator;
+
+use crate::hash::{AssemblyBuildHash, KeyHasher};
+
+/// Limits for the seed and table size search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchBounds {
+    /// Largest table tried, as slots per key; sizes start at the smallest
+    /// power of two holding every key and double up to this
+    pub max_slots_per_key: usize,
+    /// Seeds tried at each table size before doubling it
+    pub seeds_per_size: u32,
+}
+
+impl Default for SearchBounds {
+    fn default() -> Self {
+        Self {
+            max_slots_per_key: 8,
+            seeds_per_size: 4096,
+        }
+    }
+}
+
+/// Error returned when building a [`PerfectHashMap`]. Entries are identified
+/// by their position in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PerfectHashError {
+    /// Two entries have equal keys
+    DuplicateKey { first: usize, second: usize },
+    /// Two entries have different keys with the same hash, which no seed
+    /// can send to different slots
+    HashCollision { first: usize, second: usize },
+    /// No seed within the bounds gives every key its own slot
+    NotFound(SearchBounds),
+}
+
+impl fmt::Display for PerfectHashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PerfectHashError::DuplicateKey { first, second } => {
+                write!(f, "entries {first} and {second} have the same key")
+            }
+            PerfectHashError::HashCollision { first, second } => {
+                write!(f, "entries {first} and {second} have the same hash")
+            }
+            PerfectHashError::NotFound(bounds) => write!(
+                f,
+                "no perfect seed within {} seeds per size up to {} slots per key",
+                bounds.seeds_per_size, bounds.max_slots_per_key
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PerfectHashError {}
+
+/// Immutable map answering each lookup with a single slot access
+pub struct PerfectHashMap<K, V, S = AssemblyBuildHash> {
+    slots: Box<[Option<(K, V)>]>,
+    /// Odd multiplier found by the search
+    seed: u64,
+    /// `64 - log2(slots.len())`, keeping the top bits of the product
+    shift: u32,
+    len: usize,
+    hasher: S,
+}
+
+/// Slot of `hash` in a table of `64 - shift` bits: multiply-shift hashing,
+/// which keeps the well-mixed high bits of the product
+fn slot_index(hash: u32, seed: u64, shift: u32) -> usize {
+    ((hash as u64).wrapping_mul(seed) >> shift) as usize
+}
+
+/// The `n`th candidate seed: a splitmix64 step, forced odd so the multiply
+/// keeps every bit of the hash
+fn nth_seed(n: u32) -> u64 {
+    let mut z = (n as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) | 1
+}
+
+impl<K, V> PerfectHashMap<K, V>
+where
+    K: Eq,
+    AssemblyBuildHash: KeyHasher<K>,
+{
+    /// Builds the map over `entries` with the default assembly hash
+    pub fn new<I>(entries: I) -> Result<Self, PerfectHashError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        Self::with_hasher(entries, AssemblyBuildHash)
+    }
+}
+
+impl<K, V, S> PerfectHashMap<K, V, S>
+where
+    K: Eq,
+    S: KeyHasher<K>,
+{
+    pub fn with_hasher<I>(entries: I, hasher: S) -> Result<Self, PerfectHashError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        Self::with_bounds(entries, hasher, SearchBounds::default())
+    }
+
+    /// Builds the map over `entries`, searching no further than `bounds`
+    pub fn with_bounds<I>(
+        entries: I,
+        hasher: S,
+        bounds: SearchBounds,
+    ) -> Result<Self, PerfectHashError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let entries: Vec<(K, V)> = entries.into_iter().collect();
+        let hashes: Vec<u32> = entries
+            .iter()
+            .map(|(key, _)| hasher.hash_key(key))
+            .collect();
+
+        // Equal hashes defeat every seed, so report them before searching
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.sort_unstable_by_key(|&i| (hashes[i], i));
+        for pair in order.windows(2) {
+            let (first, second) = (pair[0], pair[1]);
+            if hashes[first] == hashes[second] {
+                return Err(if entries[first].0 == entries[second].0 {
+                    PerfectHashError::DuplicateKey { first, second }
+                } else {
+                    PerfectHashError::HashCollision { first, second }
+                });
+            }
+        }
+
+        let (seed, shift) =
+            Self::search(&hashes, bounds).ok_or(PerfectHashError::NotFound(bounds))?;
+        let mut slots: Box<[Option<(K, V)>]> = (0..1usize << (64 - shift)).map(|_| None).collect();
+        let len = entries.len();
+        for (entry, hash) in entries.into_iter().zip(hashes) {
+            slots[slot_index(hash, seed, shift)] = Some(entry);
+        }
+        Ok(Self {
+            slots,
+            seed,
+            shift,
+            len,
+            hasher,
+        })
+    }
+
+    /// Tries seeds at each table size from smallest to largest, returning
+    /// the first seed and shift that give every hash its own slot
+    fn search(hashes: &[u32], bounds: SearchBounds) -> Option<(u64, u32)> {
+        let min_slots = hashes.len().max(2).next_power_of_two();
+        let max_slots = hashes
+            .len()
+            .saturating_mul(bounds.max_slots_per_key)
+            .max(min_slots);
+        let mut taken = Vec::new();
+
+        let mut slots = min_slots;
+        while slots <= max_slots {
+            let shift = 64 - slots.trailing_zeros();
+            taken.resize(slots, false);
+            for n in 0..bounds.seeds_per_size {
+                let seed = nth_seed(n);
+                taken.fill(false);
+                let perfect = hashes.iter().all(|&hash| {
+                    let idx = slot_index(hash, seed, shift);
+                    !std::mem::replace(&mut taken[idx], true)
+                });
+                if perfect {
+                    return Some((seed, shift));
+                }
+            }
+            slots = slots.checked_mul(2)?;
+        }
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        let idx = slot_index(self.hasher.hash_key(key), self.seed, self.shift);
+        match &self.slots[idx] {
+            Some((k, v)) if k.borrow() == key => Some((k, v)),
+            _ => None,
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.get_key_value(key).is_some()
+    }
+}
+
+impl<K, V, S> PerfectHashMap<K, V, S> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    /// Multiplier the search settled on
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Number of slots, a power of two no smaller than the key count
+    pub fn table_len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Right shift applied to `hash * seed` to get a slot index
+    pub(crate) fn shift(&self) -> u32 {
+        self.shift
+    }
+
+    /// Every slot in table order, empty ones included
+    pub(crate) fn slots(&self) -> &[Option<(K, V)>] {
+        &self.slots
+    }
+
+    /// Iterates over the entries in slot order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.slots.iter(),
+            remaining: self.len,
+        }
+    }
+}
+
+/// Iterator over `(&K, &V)` in slot order, created by [`PerfectHashMap::iter`]
+pub struct Iter<'a, K, V> {
+    slots: std::slice::Iter<'a, Option<(K, V)>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.slots.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<'a, K, V, S> IntoIterator for &'a PerfectHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for PerfectHashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,435 @@
+//! A hash set on top of [`CustomHashMap`].
+//!
+//! Members are stored as keys with `()` values, so a bucket holds just the
+//! key and its cached hash. The set operations return lazy iterators that
+//! borrow both sets and allocate nothing.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::{Chain, FusedIterator};
+
+use crate::hash::{self, AssemblyBuildHash, AssemblyHash, CustomHashMap, KeyHasher};
+
+/// Hash set using the same open-addressing table and hashers as [`CustomHashMap`]
+pub struct CustomHashSet<T, S = AssemblyBuildHash> {
+    map: CustomHashMap<T, (), S>,
+}
+
+impl<T> CustomHashSet<T> {
+    pub fn new() -> Self {
+        Self {
+            map: CustomHashMap::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: CustomHashMap::with_capacity(capacity),
+        }
+    }
+}
+
+impl<T, S> CustomHashSet<T, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            map: CustomHashMap::with_hasher(hasher),
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            map: CustomHashMap::with_capacity_and_hasher(capacity, hasher),
+        }
+    }
+
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    /// Iterates over the members in bucket order
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.map.iter(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Keeps only the members for which `f` returns `true`
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.map.retain(|member, _| f(member));
+    }
+}
+
+impl<T, S> CustomHashSet<T, S>
+where
+    T: Eq,
+    S: KeyHasher<T>,
+{
+    /// Adds `value`, returning `false` if it was already present
+    pub fn insert(&mut self, value: T) -> bool {
+        match self.map.entry(value) {
+            hash::Entry::Occupied(_) => false,
+            hash::Entry::Vacant(entry) => {
+                entry.insert(());
+                true
+            }
+        }
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Returns the stored member equal to `value`
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.map.get_key_value(value).map(|(member, _)| member)
+    }
+
+    /// Removes `value`, returning whether it was present
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    /// Removes and returns the stored member equal to `value`
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: KeyHasher<Q>,
+    {
+        self.map.remove_entry(value).map(|(member, _)| member)
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.map.shrink_to_fit();
+    }
+
+    /// Members of `self` followed by the members of `other` not in `self`
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T, S> {
+        Union {
+            inner: self.iter().chain(other.difference(self)),
+        }
+    }
+
+    /// Members present in both sets, found by probing the larger set with
+    /// each member of the smaller one
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T, S> {
+        let (small, large) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        Intersection {
+            iter: small.iter(),
+            other: large,
+        }
+    }
+
+    /// Members of `self` that are not in `other`
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T, S> {
+        Difference {
+            iter: self.iter(),
+            other,
+        }
+    }
+
+    /// Members in exactly one of the two sets
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T, S> {
+        SymmetricDifference {
+            inner: self.difference(other).chain(other.difference(self)),
+        }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.iter().all(|member| other.contains(member))
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+/// Iterator over `&T`, created by [`CustomHashSet::iter`]
+pub struct Iter<'a, T> {
+    inner: hash::Iter<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next().map(|(member, _)| member)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Owning iterator over the members, created by [`CustomHashSet::into_iter`]
+pub struct IntoIter<T> {
+    inner: hash::IntoIter<T, ()>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next().map(|(member, _)| member)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+/// Lazy union of two sets, created by [`CustomHashSet::union`]
+pub struct Union<'a, T, S> {
+    inner: Chain<Iter<'a, T>, Difference<'a, T, S>>,
+}
+
+impl<'a, T, S> Iterator for Union<'a, T, S>
+where
+    T: Eq,
+    S: KeyHasher<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T: Eq, S: KeyHasher<T>> FusedIterator for Union<'_, T, S> {}
+
+/// Lazy intersection of two sets, created by [`CustomHashSet::intersection`]
+pub struct Intersection<'a, T, S> {
+    iter: Iter<'a, T>,
+    other: &'a CustomHashSet<T, S>,
+}
+
+impl<'a, T, S> Iterator for Intersection<'a, T, S>
+where
+    T: Eq,
+    S: KeyHasher<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let other = self.other;
+        self.iter.find(|member| other.contains(*member))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<T: Eq, S: KeyHasher<T>> FusedIterator for Intersection<'_, T, S> {}
+
+/// Lazy difference of two sets, created by [`CustomHashSet::difference`]
+pub struct Difference<'a, T, S> {
+    iter: Iter<'a, T>,
+    other: &'a CustomHashSet<T, S>,
+}
+
+impl<'a, T, S> Iterator for Difference<'a, T, S>
+where
+    T: Eq,
+    S: KeyHasher<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let other = self.other;
+        self.iter.find(|member| !other.contains(*member))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<T: Eq, S: KeyHasher<T>> FusedIterator for Difference<'_, T, S> {}
+
+/// Lazy symmetric difference of two sets, created by
+/// [`CustomHashSet::symmetric_difference`]
+pub struct SymmetricDifference<'a, T, S> {
+    inner: Chain<Difference<'a, T, S>, Difference<'a, T, S>>,
+}
+
+impl<'a, T, S> Iterator for SymmetricDifference<'a, T, S>
+where
+    T: Eq,
+    S: KeyHasher<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T: Eq, S: KeyHasher<T>> FusedIterator for SymmetricDifference<'_, T, S> {}
+
+impl<T, S> IntoIterator for CustomHashSet<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.map.into_iter(),
+        }
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a CustomHashSet<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, S: Default> Default for CustomHashSet<T, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T: Clone, S: Clone> Clone for CustomHashSet<T, S> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.map.clone_from(&source.map);
+    }
+}
+
+impl<T: fmt::Debug, S> fmt::Debug for CustomHashSet<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S> PartialEq for CustomHashSet<T, S>
+where
+    T: Eq,
+    S: KeyHasher<T>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<T, S> Eq for CustomHashSet<T, S>
+where
+    T: Eq,
+    S: KeyHasher<T>,
+{
+}
+
+impl<T, S> FromIterator<T> for CustomHashSet<T, S>
+where
+    T: Eq,
+    S: KeyHasher<T> + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T, S> Extend<T> for CustomHashSet<T, S>
+where
+    T: Eq,
+    S: KeyHasher<T>,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|member| (member, ())));
+    }
+}
+
+impl<'a, T, S> Extend<&'a T> for CustomHashSet<T, S>
+where
+    T: Eq + Copy,
+    S: KeyHasher<T>,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for CustomHashSet<T>
+where
+    T: Eq + AssemblyHash,
+{
+    fn from(members: [T; N]) -> Self {
+        Self::from_iter(members)
+    }
+}
@@ -0,0 +1,115 @@
+use custom_hashmap::codegen::LookupTable;
+use custom_hashmap::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Header {
+    Accept,
+    Authorization,
+    CacheControl,
+    ContentType,
+    Cookie,
+    Host,
+    UserAgent,
+    Referer,
+    Origin,
+    Etag,
+}
+
+mod generated {
+    use super::Header;
+
+    include!("generated/headers.rs");
+}
+
+const HEADERS: [(&str, &str); 10] = [
+    ("accept", "Header::Accept"),
+    ("authorization", "Header::Authorization"),
+    ("cache-control", "Header::CacheControl"),
+    ("content-type", "Header::ContentType"),
+    ("cookie", "Header::Cookie"),
+    ("host", "Header::Host"),
+    ("user-agent", "Header::UserAgent"),
+    ("referer", "Header::Referer"),
+    ("origin", "Header::Origin"),
+    ("etag", "Header::Etag"),
+];
+
+fn header_table() -> LookupTable {
+    LookupTable::new("Header")
+        .static_name("HEADERS")
+        .fn_name("header")
+        .entries(HEADERS)
+}
+
+#[test]
+fn test_codegen_matches_checked_in_output() {
+    // Regenerate tests/generated/headers.rs if the output format changes
+    assert_eq!(
+        header_table().generate().unwrap(),
+        include_str!("generated/headers.rs")
+    );
+}
+
+#[test]
+fn test_codegen_lookup_agrees_with_runtime_hash() {
+    use Header::*;
+
+    let expected = [
+        Accept,
+        Authorization,
+        CacheControl,
+        ContentType,
+        Cookie,
+        Host,
+        UserAgent,
+        Referer,
+        Origin,
+        Etag,
+    ];
+    for ((name, _), header) in HEADERS.iter().zip(expected) {
+        assert_eq!(generated::header(name), Some(header));
+    }
+
+    // Every string lands in the slot the runtime PerfectHashMap picks
+    let runtime =
+        PerfectHashMap::new(HEADERS.iter().zip(expected).map(|((n, _), h)| (*n, h))).unwrap();
+    for probe in [
+        "",
+        "ho",
+        "hosT",
+        "etags",
+        "x-forwarded-for",
+        "content-typo",
+        "açcept",
+    ] {
+        assert_eq!(generated::header(probe), runtime.get(&probe).copied());
+        assert_eq!(generated::header(probe), None);
+    }
+}
+
+#[test]
+fn test_codegen_reports_colliding_keywords() {
+    let err = LookupTable::new("u8")
+        .entry("loop", "0")
+        .entry("fn", "1")
+        .entry("if", "2")
+        .generate()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        PerfectHashError::HashCollision {
+            first: 1,
+            second: 2
+        }
+    );
+
+    let source = LookupTable::new("u8")
+        .entry("loop", "0")
+        .entry("fn", "1")
+        .entry("\"quoted\"", "2")
+        .generate()
+        .unwrap();
+    assert!(source.contains("static TABLE: [Option<(&str, u8)>; 4] = ["));
+    assert!(source.contains("Some((\"\\\"quoted\\\"\", 2)),"));
+    assert!(source.contains("pub fn lookup(key: &str) -> Option<u8> {"));
+}
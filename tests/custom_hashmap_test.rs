@@ -1,3 +1,4 @@
+use custom_hashmap::hash::Entry;
 use custom_hashmap::*;
 
 #[test]
@@ -168,3 +169,1359 @@ fn test_empty() {
 fn test_chache_size() {
     assert_eq!(CACHE_SIZE, 128);
 }
+
+#[test]
+fn test_map_iter() {
+    let mut map = CustomHashMap::new();
+    for i in 0..100 {
+        map.insert(i, i * 10);
+    }
+    map.remove(&7);
+
+    let iter = map.iter();
+    assert_eq!(iter.len(), 99);
+
+    let mut pairs: Vec<_> = iter.map(|(&k, &v)| (k, v)).collect();
+    pairs.sort();
+    let expected: Vec<_> = (0..100).filter(|&i| i != 7).map(|i| (i, i * 10)).collect();
+    assert_eq!(pairs, expected);
+
+    let mut keys: Vec<_> = map.keys().copied().collect();
+    keys.sort();
+    assert_eq!(keys, (0..100).filter(|&i| i != 7).collect::<Vec<_>>());
+    assert_eq!(
+        map.values().sum::<i32>(),
+        (0..100).map(|i| i * 10).sum::<i32>() - 70
+    );
+}
+
+#[test]
+fn test_map_iter_mut() {
+    let mut map = CustomHashMap::new();
+    for i in 0..10 {
+        map.insert(i, i);
+    }
+
+    for (_, v) in map.iter_mut() {
+        *v += 1;
+    }
+    for v in map.values_mut() {
+        *v *= 2;
+    }
+    for (k, v) in &mut map {
+        assert_eq!(*v, (k + 1) * 2);
+    }
+}
+
+#[test]
+fn test_map_into_iter() {
+    let mut map = CustomHashMap::new();
+    map.insert("a".to_string(), 1);
+    map.insert("bcde".to_string(), 2);
+
+    let mut count = 0;
+    for (_, v) in &map {
+        count += v;
+    }
+    assert_eq!(count, 3);
+
+    let iter = map.into_iter();
+    assert_eq!(iter.len(), 2);
+    let mut pairs: Vec<_> = iter.collect();
+    pairs.sort();
+    assert_eq!(pairs, vec![("a".to_string(), 1), ("bcde".to_string(), 2)]);
+}
+
+#[test]
+fn test_map_drain() {
+    let mut map = CustomHashMap::new();
+    for i in 0..20 {
+        map.insert(i, i);
+    }
+    map.remove(&3);
+
+    let drained: Vec<_> = map.drain().collect();
+    assert_eq!(drained.len(), 19);
+    assert!(map.is_empty());
+    assert_eq!(map.iter().count(), 0);
+
+    // A partially consumed drain still empties the map
+    map.insert(1, 1);
+    map.insert(2, 2);
+    assert_eq!(map.drain().take(1).count(), 1);
+    assert!(map.is_empty());
+    assert_eq!(map.get(&1), None);
+    assert_eq!(map.get(&2), None);
+}
+
+#[test]
+fn test_map_leaked_drain_keeps_the_rest() {
+    let mut map = CustomHashMap::new();
+    for i in 0..20 {
+        map.insert(i, i);
+    }
+
+    let mut drain = map.drain();
+    let taken: Vec<_> = drain.by_ref().take(5).map(|(k, _)| k).collect();
+    std::mem::forget(drain);
+
+    // The entries not yet yielded are still there and reachable
+    assert_eq!(map.len(), 15);
+    assert_eq!(map.iter().count(), 15);
+    for i in 0..20 {
+        let expected = (!taken.contains(&i)).then_some(i);
+        assert_eq!(map.get(&i).copied(), expected);
+    }
+    for &i in &taken {
+        map.insert(i, i + 100);
+    }
+    assert_eq!(map.len(), 20);
+    assert_eq!(map.iter().count(), 20);
+}
+
+#[test]
+fn test_map_entry_counting() {
+    let mut counts: CustomHashMap<String, usize> = CustomHashMap::new();
+    for word in ["let", "mut", "let", "const", "let", "mut"] {
+        *counts.entry(word.to_string()).or_insert(0) += 1;
+    }
+
+    assert_eq!(counts.len(), 3);
+    assert_eq!(counts.get(&"let".to_string()), Some(&3));
+    assert_eq!(counts.get(&"mut".to_string()), Some(&2));
+    assert_eq!(counts.get(&"const".to_string()), Some(&1));
+}
+
+#[test]
+fn test_map_entry_and_modify() {
+    let mut map: CustomHashMap<i32, Vec<i32>> = CustomHashMap::new();
+    map.entry(1).and_modify(|v| v.push(0)).or_default().push(1);
+    map.entry(1).and_modify(|v| v.push(2)).or_default();
+    assert_eq!(map.get(&1), Some(&vec![1, 2]));
+
+    assert_eq!(
+        *map.entry(2).or_insert_with_key(|k| vec![*k * 10]),
+        vec![20]
+    );
+    assert_eq!(map.entry(2).key(), &2);
+}
+
+#[test]
+fn test_map_entry_occupied_and_vacant() {
+    let mut map = CustomHashMap::new();
+    map.insert(1, "one");
+
+    match map.entry(1) {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(entry.key(), &1);
+            assert_eq!(entry.insert("uno"), "one");
+            assert_eq!(entry.remove(), "uno");
+        }
+        Entry::Vacant(_) => panic!("expected occupied entry"),
+    }
+    assert!(!map.contains_key(&1));
+    assert!(map.is_empty());
+
+    match map.entry(2) {
+        Entry::Vacant(entry) => {
+            assert_eq!(entry.key(), &2);
+            *entry.insert("two") = "dos";
+        }
+        Entry::Occupied(_) => panic!("expected vacant entry"),
+    }
+    assert_eq!(map.get(&2), Some(&"dos"));
+    assert_eq!(map.len(), 1);
+
+    let entry = map.entry(3).insert_entry("three");
+    assert_eq!(entry.get(), &"three");
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_map_borrowed_lookup() {
+    let mut map: CustomHashMap<String, i32> = CustomHashMap::new();
+    map.insert("alpha".to_string(), 1);
+    map.insert("be".to_string(), 2);
+
+    assert_eq!(map.get("alpha"), Some(&1));
+    assert_eq!(map.get("be"), Some(&2));
+    assert!(map.contains_key("alpha"));
+    assert!(!map.contains_key("gamma"));
+
+    if let Some(v) = map.get_mut("alpha") {
+        *v = 10;
+    }
+    assert_eq!(map.remove("alpha"), Some(10));
+    assert_eq!(map.get("alpha"), None);
+}
+
+#[test]
+fn test_borrowed_forms_hash_alike() {
+    for s in ["", "ab", "abc", "abcd", "hello world"] {
+        let owned = s.to_string();
+        assert_eq!(owned.assembly_hash(), s.assembly_hash());
+        assert_eq!(owned.assembly_hash(), AssemblyHash::assembly_hash(s));
+    }
+}
+
+#[test]
+fn test_lru_borrowed_lookup() {
+    let mut cache = LRUCache::new(2);
+    cache.insert("foo".to_string(), 1);
+    cache.insert("bar".to_string(), 2);
+
+    assert!(cache.contains("foo"));
+    assert_eq!(cache.get("foo"), Some(&1));
+    assert_eq!(cache.remove("bar"), Some(2));
+    assert!(!cache.contains("bar"));
+}
+
+#[test]
+fn test_map_insert_after_tombstone_keeps_keys_unique() {
+    // 1 and 17 share a home bucket in the 16-bucket table for 12 entries
+    let mut map = CustomHashMap::with_capacity(12);
+    map.insert(1, "a");
+    map.insert(17, "b");
+    map.remove(&1);
+
+    assert_eq!(map.insert(17, "c"), Some("b"));
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get(&17), Some(&"c"));
+    assert_eq!(map.iter().count(), 1);
+
+    *map.entry(17).or_insert("d") = "e";
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.remove(&17), Some("e"));
+    assert_eq!(map.get(&17), None);
+}
+
+#[test]
+fn test_map_heavy_churn_terminates() {
+    let mut map = CustomHashMap::with_capacity(16);
+    for i in 0..10_000 {
+        map.insert(i, i);
+        if i >= 4 {
+            assert_eq!(map.remove(&(i - 4)), Some(i - 4));
+        }
+    }
+
+    assert_eq!(map.len(), 4);
+    assert_eq!(map.get(&9_999), Some(&9_999));
+    assert_eq!(map.get(&0), None);
+    assert_eq!(map.get(&123_456), None);
+}
+
+#[test]
+fn test_map_backward_shift_keeps_cluster_reachable() {
+    let mut map =
+        CustomHashMap::with_capacity_and_deletion_strategy(12, DeletionStrategy::BackwardShift);
+    assert_eq!(map.deletion_strategy(), DeletionStrategy::BackwardShift);
+
+    // 15, 31 and 47 share home bucket 15, so the cluster wraps around to 0 and 1
+    map.insert(15, 'a');
+    map.insert(31, 'b');
+    map.insert(0, 'c');
+    map.insert(47, 'd');
+
+    assert_eq!(map.remove(&15), Some('a'));
+    assert_eq!(map.get(&31), Some(&'b'));
+    assert_eq!(map.get(&0), Some(&'c'));
+    assert_eq!(map.get(&47), Some(&'d'));
+
+    assert_eq!(map.remove(&0), Some('c'));
+    assert_eq!(map.get(&31), Some(&'b'));
+    assert_eq!(map.get(&47), Some(&'d'));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_map_backward_shift_churn() {
+    let mut map = CustomHashMap::with_deletion_strategy(DeletionStrategy::BackwardShift);
+    let mut expected = std::collections::HashMap::new();
+
+    for i in 0..5_000u32 {
+        // Multiples of 16 collide heavily in the identity hash
+        let key = (i * 7919) % 97 * 16;
+        if i % 3 == 0 {
+            assert_eq!(map.remove(&key), expected.remove(&key));
+        } else {
+            assert_eq!(map.insert(key, i), expected.insert(key, i));
+        }
+    }
+
+    assert_eq!(map.len(), expected.len());
+    for (k, v) in &expected {
+        assert_eq!(map.get(k), Some(v));
+    }
+}
+
+#[test]
+fn test_map_robin_hood_matches_std() {
+    let mut map = CustomHashMap::with_probe_strategy(ProbeStrategy::RobinHood);
+    assert_eq!(map.probe_strategy(), ProbeStrategy::RobinHood);
+    assert_eq!(map.deletion_strategy(), DeletionStrategy::BackwardShift);
+
+    let mut expected = std::collections::HashMap::new();
+    for i in 0..5_000u32 {
+        // Mix clustered keys (multiples of 16) with scattered ones
+        let key = if i % 2 == 0 {
+            (i * 7919) % 131 * 16
+        } else {
+            i.wrapping_mul(0x9E37_79B1) % 4096
+        };
+        match i % 5 {
+            0 | 1 => assert_eq!(map.remove(&key), expected.remove(&key)),
+            _ => assert_eq!(map.insert(key, i), expected.insert(key, i)),
+        }
+        assert_eq!(map.get(&(key + 1)), expected.get(&(key + 1)));
+    }
+
+    assert_eq!(map.len(), expected.len());
+    for (k, v) in &expected {
+        assert_eq!(map.get(k), Some(v));
+    }
+}
+
+#[test]
+fn test_map_robin_hood_entry_and_clone() {
+    let mut map = CustomHashMap::with_capacity_and_probe_strategy(16, ProbeStrategy::RobinHood);
+    for key in [3, 19, 35, 4, 20, 5] {
+        *map.entry(key).or_insert(0) += key;
+    }
+    *map.entry(19).or_insert(0) += 1;
+
+    let copy = map.clone();
+    assert_eq!(copy.probe_strategy(), ProbeStrategy::RobinHood);
+    for key in [3, 35, 4, 20, 5] {
+        assert_eq!(copy.get(&key), Some(&key));
+    }
+    assert_eq!(copy.get(&19), Some(&20));
+    assert_eq!(copy.get(&51), None);
+}
+
+#[test]
+fn test_map_small_tables_wrap_groups() {
+    // Capacities this small give tables narrower than a 16-byte control
+    // group, which wrap around within one load
+    for capacity in [0, 1, 2, 3, 6] {
+        for (deletion, probe) in [
+            (DeletionStrategy::Tombstone, ProbeStrategy::Linear),
+            (DeletionStrategy::BackwardShift, ProbeStrategy::Linear),
+            (DeletionStrategy::BackwardShift, ProbeStrategy::RobinHood),
+        ] {
+            let mut map = if probe == ProbeStrategy::RobinHood {
+                CustomHashMap::with_capacity_and_probe_strategy(capacity, probe)
+            } else {
+                CustomHashMap::with_capacity_and_deletion_strategy(capacity, deletion)
+            };
+            let mut expected = std::collections::HashMap::new();
+
+            for i in 0..200u32 {
+                let key = (i * 37) % 23;
+                if i % 4 == 0 {
+                    assert_eq!(map.remove(&key), expected.remove(&key));
+                } else {
+                    assert_eq!(map.insert(key, i), expected.insert(key, i));
+                }
+                assert_eq!(map.get(&(key + 5)), expected.get(&(key + 5)));
+            }
+            assert_eq!(map.len(), expected.len());
+        }
+    }
+}
+
+#[test]
+fn test_map_drops_each_entry_once() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+    use std::rc::Rc;
+
+    let token = Rc::new(());
+    let live = || Rc::strong_count(&token) - 1;
+
+    for probe in [ProbeStrategy::Linear, ProbeStrategy::RobinHood] {
+        let mut map = CustomHashMap::with_probe_strategy(probe);
+        for i in 0..100 {
+            map.insert(i, token.clone());
+        }
+        for i in (0..100).step_by(4) {
+            map.remove(&i);
+        }
+        map.insert(7, token.clone());
+        map.retain(|k, _| k % 5 != 0);
+        assert_eq!(live(), map.len());
+
+        let mut copy = map.clone();
+        assert_eq!(live(), 2 * map.len());
+        copy.clear();
+        assert_eq!(live(), map.len());
+        copy.insert(1, token.clone());
+        drop(copy);
+
+        let mut target = CustomHashMap::with_probe_strategy(probe);
+        for i in 0..60 {
+            target.insert(i, token.clone());
+        }
+        target.clone_from(&map);
+        assert_eq!(live(), 2 * map.len());
+        drop(target);
+
+        let mut iter = map.clone().into_iter();
+        iter.next();
+        drop(iter);
+        map.drain().take(3).for_each(drop);
+        assert_eq!(live(), 0);
+    }
+
+    // Cleared, drained or dropped mid-resize, with entries in both tables
+    let growing = || {
+        let mut map = CustomHashMapBuilder::new()
+            .resize_strategy(ResizeStrategy::Incremental { min_batch: 1 })
+            .build();
+        while !map.is_resizing() {
+            let next = map.len() as u32;
+            map.insert(next, token.clone());
+        }
+        map
+    };
+    let mut map = growing();
+    let copy = map.clone();
+    map.clear();
+    assert_eq!(live(), copy.len());
+    drop(copy);
+    growing().drain().take(3).for_each(drop);
+    drop(growing());
+    assert_eq!(live(), 0);
+
+    // A panicking clone leaves nothing half-owned behind
+    struct Fragile(Rc<()>, bool);
+
+    impl Clone for Fragile {
+        fn clone(&self) -> Self {
+            assert!(!self.1, "fragile value cloned");
+            Fragile(self.0.clone(), self.1)
+        }
+    }
+
+    let mut map = CustomHashMap::new();
+    for i in 0..50 {
+        map.insert(i, Fragile(token.clone(), i == 25));
+    }
+    assert!(catch_unwind(AssertUnwindSafe(|| map.clone())).is_err());
+    assert_eq!(live(), 50);
+    drop(map);
+    assert_eq!(live(), 0);
+}
+
+#[test]
+fn test_map_many_string_keys() {
+    let mut map = CustomHashMap::new();
+    let keys: Vec<String> = (0..2_000).map(|i| format!("key_{i}_end")).collect();
+    for (i, key) in keys.iter().enumerate() {
+        map.insert(key.clone(), i);
+    }
+    for (i, key) in keys.iter().enumerate().step_by(3) {
+        assert_eq!(map.remove(key.as_str()), Some(i));
+    }
+
+    for (i, key) in keys.iter().enumerate() {
+        let expected = if i % 3 == 0 { None } else { Some(&i) };
+        assert_eq!(map.get(key.as_str()), expected);
+    }
+    assert_eq!(map.get("key_missing_end"), None);
+}
+
+#[test]
+fn test_map_reserve() {
+    let mut map = CustomHashMap::new();
+    assert_eq!(map.capacity(), 12);
+
+    map.reserve(1_000);
+    let capacity = map.capacity();
+    assert!(capacity >= 1_000);
+
+    for i in 0..1_000 {
+        map.insert(i, i);
+    }
+    assert_eq!(map.capacity(), capacity);
+    assert_eq!(map.len(), 1_000);
+    assert_eq!(map.get(&999), Some(&999));
+}
+
+#[test]
+fn test_map_reserve_never_shrinks() {
+    let mut map = CustomHashMap::new();
+    for i in 0..700 {
+        map.insert(i, i);
+    }
+    for i in 0..690 {
+        map.remove(&i);
+    }
+    let capacity = map.capacity();
+    assert_eq!(capacity, 768);
+
+    // Past the tombstone threshold, so this compacts, but in place
+    map.reserve(1);
+    assert_eq!(map.capacity(), capacity);
+    map.insert(1_000, 1_000);
+    assert_eq!(map.capacity(), capacity);
+    assert_eq!(map.len(), 11);
+    assert!((690..700).all(|i| map.get(&i) == Some(&i)));
+
+    map.shrink_to_fit();
+    assert!(map.capacity() < capacity);
+}
+
+#[test]
+fn test_map_try_reserve() {
+    let mut map: CustomHashMap<u64, u64> = CustomHashMap::new();
+    map.insert(1, 1);
+
+    assert_eq!(map.try_reserve(100), Ok(()));
+    assert!(map.capacity() >= 101);
+    assert_eq!(
+        map.try_reserve(usize::MAX),
+        Err(TryReserveError::CapacityOverflow)
+    );
+    assert_eq!(
+        map.try_reserve(usize::MAX / 8),
+        Err(TryReserveError::CapacityOverflow)
+    );
+
+    // A failed reservation leaves the map usable
+    assert_eq!(map.get(&1), Some(&1));
+    map.insert(2, 2);
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_map_shrink() {
+    let mut map = CustomHashMap::new();
+    for i in 0..10_000 {
+        map.insert(i, i);
+    }
+    for i in 100..10_000 {
+        map.remove(&i);
+    }
+    let bulk_capacity = map.capacity();
+
+    map.shrink_to(1_000);
+    assert!(map.capacity() >= 1_000);
+    assert!(map.capacity() < bulk_capacity);
+
+    map.shrink_to_fit();
+    assert!(map.capacity() >= 100);
+    assert!(map.capacity() < 1_000);
+
+    assert_eq!(map.len(), 100);
+    for i in 0..100 {
+        assert_eq!(map.get(&i), Some(&i));
+    }
+    assert_eq!(map.get(&100), None);
+
+    // Shrinking never drops below the current length
+    map.shrink_to(0);
+    assert!(map.capacity() >= map.len());
+    map.insert(100, 100);
+    assert_eq!(map.get(&100), Some(&100));
+}
+
+#[test]
+fn test_map_builder_defaults() {
+    let map: CustomHashMap<u32, u32> = CustomHashMapBuilder::new().build();
+    assert_eq!(map.max_load_factor(), 0.75);
+    assert_eq!(map.tombstone_threshold(), 0.25);
+    assert_eq!(map.growth_multiplier(), 2);
+    assert_eq!(map.deletion_strategy(), DeletionStrategy::Tombstone);
+    assert_eq!(map.probe_strategy(), ProbeStrategy::Linear);
+    assert_eq!(map.capacity(), CustomHashMap::<u32, u32>::new().capacity());
+}
+
+#[test]
+fn test_map_with_capacity_counts_entries() {
+    for capacity in [0, 1, 12, 13, 100, 1_000] {
+        let mut map = CustomHashMap::with_capacity(capacity);
+        let initial = map.capacity();
+        assert!(initial >= capacity);
+        for i in 0..capacity as u32 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.capacity(), initial, "with_capacity({capacity}) grew");
+    }
+    assert_eq!(
+        CustomHashMap::<u32, u32>::with_capacity(100).capacity(),
+        192
+    );
+    assert_eq!(CustomHashSet::<u32>::with_capacity(100).capacity(), 192);
+    assert_eq!(CustomHashMap::<u32, u32>::new().capacity(), 12);
+
+    let sparse: CustomHashMap<u32, u32> = CustomHashMapBuilder::new()
+        .initial_capacity(100)
+        .max_load_factor(0.5)
+        .build();
+    assert_eq!(sparse.capacity(), 128);
+}
+
+#[test]
+fn test_map_builder_load_factor() {
+    // Both need 64 slots to hold 32 entries
+    let mut dense = CustomHashMapBuilder::new()
+        .initial_capacity(32)
+        .max_load_factor(0.9)
+        .build();
+    let mut sparse = CustomHashMapBuilder::new()
+        .initial_capacity(32)
+        .max_load_factor(0.5)
+        .build();
+    assert_eq!(dense.capacity(), 57);
+    assert_eq!(sparse.capacity(), 32);
+
+    // 900 entries fit 1024 slots at 90% but need 2048 at 50%
+    for i in 0..900u32 {
+        dense.insert(i, i);
+        sparse.insert(i, i);
+    }
+    assert_eq!(dense.capacity(), 921);
+    assert_eq!(sparse.capacity(), 1024);
+    for i in 0..900 {
+        assert_eq!(dense.get(&i), Some(&i));
+        assert_eq!(sparse.get(&i), Some(&i));
+    }
+
+    sparse.reserve(5_000);
+    assert!(sparse.capacity() >= 5_900);
+}
+
+#[test]
+fn test_map_builder_growth_and_tombstones() {
+    let mut map = CustomHashMapBuilder::new()
+        .initial_capacity(12)
+        .growth_multiplier(4)
+        .tombstone_threshold(0.5)
+        .probe_strategy(ProbeStrategy::RobinHood)
+        .build();
+    assert_eq!(map.growth_multiplier(), 4);
+    assert_eq!(map.deletion_strategy(), DeletionStrategy::BackwardShift);
+
+    for i in 0..13 {
+        map.insert(i, i);
+    }
+    // 16 slots at 75% hold 12 entries, so the 13th grows the table 4x
+    assert_eq!(map.capacity(), 48);
+
+    let mut churn = CustomHashMapBuilder::new().tombstone_threshold(0.5).build();
+    for i in 0..5_000 {
+        churn.insert(i, i);
+        churn.remove(&i);
+    }
+    assert!(churn.is_empty());
+    assert_eq!(churn.capacity(), 12);
+}
+
+#[test]
+#[should_panic(expected = "max load factor")]
+fn test_map_builder_rejects_full_load_factor() {
+    let _ = CustomHashMapBuilder::new().max_load_factor(1.0);
+}
+
+#[test]
+#[should_panic(expected = "power of two")]
+fn test_map_builder_rejects_odd_growth_multiplier() {
+    let _ = CustomHashMapBuilder::new().growth_multiplier(3);
+}
+
+#[test]
+#[should_panic(expected = "requires BackwardShift")]
+fn test_map_builder_rejects_robin_hood_tombstones() {
+    let _: CustomHashMap<u32, u32> = CustomHashMapBuilder::new()
+        .deletion_strategy(DeletionStrategy::Tombstone)
+        .probe_strategy(ProbeStrategy::RobinHood)
+        .build();
+}
+
+#[test]
+fn test_map_multiplicative_hasher() {
+    let mut map = CustomHashMap::with_hasher(MultiplicativeHash);
+    // Identity hashing puts every multiple of 1024 in the same home slot
+    for i in 0..2_000u32 {
+        map.insert(i * 1024, i);
+    }
+    assert_eq!(map.len(), 2_000);
+    for i in 0..2_000u32 {
+        assert_eq!(map.get(&(i * 1024)), Some(&i));
+    }
+    assert_eq!(map.remove(&1024), Some(1));
+    assert!(!map.contains_key(&1024));
+
+    assert_eq!(MultiplicativeHash.hash_key("abcd"), {
+        let raw = "abcd".assembly_hash() as u64;
+        (raw.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as u32
+    });
+}
+
+#[test]
+fn test_map_full_content_hasher() {
+    // The default hash only sees "co" and "on"
+    assert_eq!(
+        "configuration".assembly_hash(),
+        "coordination".assembly_hash()
+    );
+    assert_ne!(
+        FullContentHash.hash_key("configuration"),
+        FullContentHash.hash_key("coordination")
+    );
+    // Owned and borrowed strings agree, and trailing zero bytes still count
+    let owned = "src/hash.rs".to_string();
+    assert_eq!(
+        FullContentHash.hash_key(&owned),
+        FullContentHash.hash_key("src/hash.rs")
+    );
+    assert_eq!(
+        FullContentHash.hash_key(&"src/hash.rs"),
+        FullContentHash.hash_key("src/hash.rs")
+    );
+    assert_ne!(
+        FullContentHash.hash_key("ab\0"),
+        FullContentHash.hash_key("ab")
+    );
+
+    // Paths sharing their first and last two bytes
+    let paths: Vec<String> = (0..1_000).map(|i| format!("/srv/{i}/index.html")).collect();
+    let distinct = |hash: &dyn Fn(&str) -> u32| {
+        let mut hashes: Vec<u32> = paths.iter().map(|p| hash(p)).collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        hashes.len()
+    };
+    assert_eq!(distinct(&|p| p.assembly_hash()), 1);
+    assert_eq!(distinct(&|p| FullContentHash.hash_key(p)), 1_000);
+
+    let mut map = CustomHashMap::with_hasher(FullContentHash);
+    for (i, path) in paths.iter().enumerate() {
+        map.insert(path.clone(), i);
+    }
+    assert_eq!(map.get("/srv/500/index.html"), Some(&500));
+    assert_eq!(map.remove("/srv/7/index.html"), Some(7));
+    assert_eq!(map.len(), 999);
+
+    let mut ints = CustomHashMap::with_hasher(FullContentHash);
+    for i in 0..2_000u32 {
+        ints.insert(i * 1024, i);
+    }
+    assert!((0..2_000u32).all(|i| ints.get(&(i * 1024)) == Some(&i)));
+}
+
+#[test]
+fn test_map_std_build_hasher() {
+    use std::collections::hash_map::RandomState;
+
+    let mut map = CustomHashMapBuilder::new()
+        .probe_strategy(ProbeStrategy::RobinHood)
+        .build_with_hasher(RandomState::new());
+    *map.entry("ident_1_x".to_string()).or_insert(0) += 1;
+    *map.entry("ident_1_x".to_string()).or_insert(0) += 1;
+    map.insert("ident_2_x".to_string(), 7);
+    assert_eq!(map.get("ident_1_x"), Some(&2));
+    assert_eq!(map.get("ident_2_x"), Some(&7));
+
+    let cloned = map.clone();
+    assert_eq!(cloned.len(), 2);
+    assert_eq!(cloned.get("ident_1_x"), Some(&2));
+    assert_eq!(
+        cloned.hasher().hash_key("ident_2_x"),
+        map.hasher().hash_key("ident_2_x")
+    );
+
+    let default: CustomHashMap<u32, u32, AssemblyBuildHash> = CustomHashMap::default();
+    assert_eq!(default.hasher(), &AssemblyBuildHash);
+}
+
+#[test]
+fn test_map_std_traits() {
+    let map = CustomHashMap::from([("alpha", 1), ("beta", 2)]);
+    assert_eq!(map["alpha"], 1);
+    assert_eq!(map.get_key_value("beta"), Some((&"beta", &2)));
+    assert_eq!(
+        format!("{:?}", CustomHashMap::from([(7u32, 'x')])),
+        "{7: 'x'}"
+    );
+
+    let mut collected: CustomHashMap<&str, i32> = [("beta", 2), ("alpha", 1)].into_iter().collect();
+    assert_eq!(collected, map);
+    collected.extend([("gamma", 3)]);
+    assert_ne!(collected, map);
+    collected.extend(map.iter().map(|(&k, &v)| (k, v * 10)));
+    assert_eq!(collected.len(), 3);
+    assert_eq!(collected["alpha"], 10);
+
+    let mut copied = CustomHashMap::new();
+    copied.extend(&collected);
+    assert_eq!(copied, collected);
+}
+
+#[test]
+#[should_panic(expected = "key not found")]
+fn test_map_index_missing_key() {
+    let map: CustomHashMap<String, i32> = CustomHashMap::new();
+    let _ = map["missing"];
+}
+
+#[test]
+fn test_map_remove_entry_clear_try_insert() {
+    let mut map = CustomHashMap::new();
+    map.insert("key".to_string(), 1);
+    assert_eq!(map.remove_entry("key"), Some(("key".to_string(), 1)));
+    assert_eq!(map.remove_entry("key"), None);
+
+    *map.try_insert("key".to_string(), 2).unwrap() += 1;
+    let err = map.try_insert("key".to_string(), 9).unwrap_err();
+    assert_eq!(err.value, 9);
+    assert_eq!(err.entry.get(), &3);
+    assert_eq!(
+        err.to_string(),
+        "failed to insert 9, key \"key\" already exists with value 3"
+    );
+
+    for i in 0..100 {
+        map.insert(format!("k{i}"), i);
+    }
+    let capacity = map.capacity();
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.capacity(), capacity);
+    assert_eq!(map.get("k5"), None);
+    map.insert("k5".to_string(), 5);
+    assert_eq!(map.iter().count(), 1);
+}
+
+/// xorshift32, so the differential test needs no rand dependency
+struct XorShift(u32);
+
+impl XorShift {
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+}
+
+fn differential_run(mut map: CustomHashMap<u32, u32>, seed: u32) {
+    use std::collections::HashMap;
+
+    let mut rng = XorShift(seed);
+    let mut model = HashMap::new();
+    for step in 0..20_000 {
+        // A narrow key range with clustered low bits keeps probe chains and
+        // tombstones busy
+        let key = (rng.next() % 300) * 16;
+        let value = rng.next();
+        match rng.next() % 10 {
+            0..=2 => assert_eq!(map.insert(key, value), model.insert(key, value)),
+            3..=4 => assert_eq!(map.remove(&key), model.remove(&key)),
+            5 => assert_eq!(map.remove_entry(&key), model.remove_entry(&key)),
+            6 => {
+                *map.entry(key).or_insert(0) += 1;
+                *model.entry(key).or_insert(0) += 1;
+            }
+            7 => {
+                let vacant = !model.contains_key(&key);
+                if vacant {
+                    model.insert(key, value);
+                }
+                assert_eq!(map.try_insert(key, value).is_ok(), vacant);
+            }
+            8 => assert_eq!(map.get_key_value(&key), model.get_key_value(&key)),
+            _ if step % 5_000 == 9 => {
+                map.clear();
+                model.clear();
+            }
+            _ => assert_eq!(map.contains_key(&key), model.contains_key(&key)),
+        }
+        assert_eq!(map.len(), model.len());
+    }
+
+    let mut entries: Vec<_> = map.into_iter().collect();
+    let mut expected: Vec<_> = model.into_iter().collect();
+    entries.sort_unstable();
+    expected.sort_unstable();
+    assert_eq!(entries, expected);
+}
+
+#[test]
+fn test_map_differential_against_std() {
+    for seed in [1, 0xDEAD_BEEF, 0x1234_5678] {
+        differential_run(CustomHashMap::new(), seed);
+        differential_run(
+            CustomHashMap::with_deletion_strategy(DeletionStrategy::BackwardShift),
+            seed,
+        );
+        differential_run(
+            CustomHashMap::with_probe_strategy(ProbeStrategy::RobinHood),
+            seed,
+        );
+        differential_run(
+            CustomHashMapBuilder::new()
+                .max_load_factor(0.9)
+                .tombstone_threshold(0.05)
+                .build(),
+            seed,
+        );
+        differential_run(
+            CustomHashMapBuilder::new()
+                .resize_strategy(ResizeStrategy::Incremental { min_batch: 1 })
+                .build(),
+            seed,
+        );
+        differential_run(
+            CustomHashMapBuilder::new()
+                .probe_strategy(ProbeStrategy::RobinHood)
+                .resize_strategy(ResizeStrategy::Incremental { min_batch: 3 })
+                .build(),
+            seed,
+        );
+    }
+}
+
+#[test]
+fn test_map_retain_and_extract_if() {
+    for deletion in [DeletionStrategy::Tombstone, DeletionStrategy::BackwardShift] {
+        let mut map = CustomHashMap::with_deletion_strategy(deletion);
+        // Clustered keys so backward shifts move entries during the scan
+        for i in 0..500u32 {
+            map.insert(i * 16, i);
+        }
+
+        let mut visited = 0;
+        map.retain(|_, v| {
+            visited += 1;
+            *v += 1;
+            *v % 2 == 0
+        });
+        assert_eq!(visited, 500);
+        assert_eq!(map.len(), 250);
+        assert!(map.values().all(|v| v % 2 == 0));
+
+        let mut extracted: Vec<_> = map.extract_if(|k, _| *k < 1600).collect();
+        extracted.sort_unstable();
+        let expected: Vec<_> = (1..100u32).step_by(2).map(|i| (i * 16, i + 1)).collect();
+        assert_eq!(extracted, expected);
+        assert_eq!(map.len(), 200);
+        assert!(map.keys().all(|k| *k >= 1600));
+
+        // Dropping the iterator early keeps the unvisited entries
+        assert!(map.extract_if(|_, _| true).next().is_some());
+        assert_eq!(map.len(), 199);
+    }
+}
+
+#[test]
+fn test_lru_retain_and_extract_if() {
+    let mut cache = LRUCache::new(4);
+    for i in 1..=4 {
+        cache.insert(i, i * 10);
+    }
+    cache.get(&1);
+
+    let mut order = Vec::new();
+    cache.retain(|k, v| {
+        order.push(*k);
+        *v += 1;
+        k % 2 == 1
+    });
+    assert_eq!(order, vec![1, 4, 3, 2]);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&3), Some(&31));
+
+    // 1 is now least recently used and is evicted first
+    cache.insert(5, 50);
+    cache.insert(7, 70);
+    cache.insert(9, 90);
+    assert!(!cache.contains(&1));
+
+    let extracted: Vec<_> = cache.extract_if(|k, _| *k > 5).collect();
+    assert_eq!(extracted, vec![(9, 90), (7, 70)]);
+    assert_eq!(cache.len(), 2);
+    cache.insert(11, 110);
+    cache.insert(13, 130);
+    cache.insert(15, 150);
+    assert!(!cache.contains(&3));
+    assert!(cache.contains(&5));
+}
+
+#[test]
+fn test_map_get_many_mut() {
+    let mut balances = CustomHashMap::from([
+        ("alice".to_string(), 100),
+        ("bob".to_string(), 50),
+        ("carol".to_string(), 0),
+    ]);
+
+    let [alice, bob] = balances.get_many_mut(["alice", "bob"]).unwrap();
+    *alice -= 30;
+    *bob += 30;
+    assert_eq!(balances["alice"], 70);
+    assert_eq!(balances["bob"], 80);
+
+    let [a, b, c] = balances.get_many_mut(["carol", "alice", "bob"]).unwrap();
+    std::mem::swap(a, b);
+    *c += 1;
+    assert_eq!(balances["carol"], 70);
+    assert_eq!(balances["bob"], 81);
+
+    assert!(balances.get_many_mut(["alice", "alice"]).is_none());
+    assert!(balances.get_many_mut(["alice", "dave"]).is_none());
+    assert_eq!(balances.get_many_mut::<str, 0>([]), Some([]));
+}
+
+#[test]
+fn test_map_raw_api() {
+    let source = b"let x = let_y + x";
+    let mut symbols: CustomHashMap<String, u32> = CustomHashMap::new();
+
+    // Hash each token once and reuse it for both the lookup and the insert
+    for token in source.split(|&b| b == b' ') {
+        let hash = assembly_hash_bytes(token);
+        match symbols.find_mut(hash, |k| k.as_bytes() == token) {
+            Some((_, count)) => *count += 1,
+            None => {
+                let key = String::from_utf8(token.to_vec()).unwrap();
+                assert_eq!(hash, key.assembly_hash());
+                symbols.insert_with_hash(hash, key, 1);
+            }
+        }
+    }
+    assert_eq!(symbols.len(), 5);
+    assert_eq!(symbols.get("x"), Some(&2));
+    assert_eq!(symbols.get("let_y"), Some(&1));
+
+    let hash = "let".assembly_hash();
+    assert_eq!(
+        symbols.find(hash, |k| k == "let"),
+        Some((&"let".to_string(), &1))
+    );
+    assert_eq!(symbols.find(hash, |k| k == "missing"), None);
+    assert_eq!(
+        symbols.remove_by_hash(hash, |k| k.as_bytes() == b"let"),
+        Some(("let".to_string(), 1))
+    );
+    assert!(!symbols.contains_key("let"));
+    assert_eq!(symbols.insert_with_hash(hash, "let".to_string(), 3), None);
+    assert_eq!(
+        symbols.insert_with_hash(hash, "let".to_string(), 4),
+        Some(3)
+    );
+}
+
+#[test]
+fn test_map_cached_hash_skips_rehash_and_eq() {
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Identity hasher that counts its calls
+    struct CountingHasher<'a>(&'a Cell<usize>);
+
+    impl KeyHasher<u32> for CountingHasher<'_> {
+        fn hash_key(&self, key: &u32) -> u32 {
+            self.0.set(self.0.get() + 1);
+            *key
+        }
+    }
+
+    let calls = Cell::new(0);
+    let mut map = CustomHashMap::with_hasher(CountingHasher(&calls));
+    for i in 0..1_000 {
+        map.insert(i, i);
+    }
+    map.shrink_to_fit();
+    // One hash per insert, none while growing or shrinking
+    assert_eq!(calls.get(), 1_000);
+
+    static EQ_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Clone)]
+    struct Key(u32);
+
+    impl PartialEq for Key {
+        fn eq(&self, other: &Self) -> bool {
+            EQ_CALLS.fetch_add(1, Ordering::Relaxed);
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Key {}
+
+    impl AssemblyHash for Key {
+        fn assembly_hash(&self) -> u32 {
+            self.0
+        }
+    }
+
+    let mut keys = CustomHashMap::new();
+    for i in 0..1_000 {
+        keys.insert(Key(i), i);
+    }
+    EQ_CALLS.store(0, Ordering::Relaxed);
+    for i in 1_000..5_000 {
+        assert!(!keys.contains_key(&Key(i)));
+    }
+    // Distinct hashes never reach K::eq, even when their fragments collide
+    assert_eq!(EQ_CALLS.load(Ordering::Relaxed), 0);
+    assert_eq!(keys.get(&Key(7)), Some(&7));
+    assert_eq!(EQ_CALLS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_map_incremental_resize() {
+    for probe in [ProbeStrategy::Linear, ProbeStrategy::RobinHood] {
+        let mut map = CustomHashMapBuilder::new()
+            .initial_capacity(64)
+            .probe_strategy(probe)
+            .resize_strategy(ResizeStrategy::Incremental { min_batch: 4 })
+            .build();
+        assert_eq!(
+            map.resize_strategy(),
+            ResizeStrategy::Incremental { min_batch: 4 }
+        );
+
+        let mut saw_resize = false;
+        for i in 0..2_000u32 {
+            map.insert(i, i);
+            if map.is_resizing() {
+                saw_resize = true;
+                // Every entry stays reachable while it sits in either table
+                assert!((0..=i).all(|k| map.get(&k) == Some(&k)));
+                assert_eq!(map.iter().count(), map.len());
+            }
+        }
+        assert!(saw_resize);
+        assert_eq!(map.len(), 2_000);
+
+        // Grow once more and work on entries still in the old table
+        while !map.is_resizing() {
+            let next = map.len() as u32;
+            map.insert(next, next);
+        }
+        let len = map.len() as u32;
+        assert_eq!(map.remove(&(len - 2)), Some(len - 2));
+        *map.get_mut(&7).unwrap() += 100;
+        let [a, b] = map.get_many_mut([&1, &(len - 1)]).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(map.get(&7), Some(&107));
+        assert_eq!(map.get(&1), Some(&(len - 1)));
+        assert!(!map.contains_key(&(len - 2)));
+        assert_eq!(map.len() as u32, len - 1);
+
+        // Whole-table operations finish the migration first
+        let mut keys: Vec<_> = map.clone().into_iter().map(|(k, _)| k).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len() as u32, len - 1);
+        map.retain(|k, _| k % 2 == 0);
+        assert!(!map.is_resizing());
+        assert!(map.keys().all(|k| k % 2 == 0));
+    }
+}
+
+#[test]
+fn test_map_incremental_resize_spreads_migration() {
+    for probe in [ProbeStrategy::Linear, ProbeStrategy::RobinHood] {
+        let mut map = CustomHashMapBuilder::new()
+            .probe_strategy(probe)
+            .resize_strategy(ResizeStrategy::Incremental { min_batch: 1 })
+            .build();
+
+        let mut resizes = 0;
+        let mut started = None;
+        for i in 0..300_000u32 {
+            let (capacity, resizing) = (map.capacity(), map.is_resizing());
+            map.insert(i, i);
+            if map.capacity() != capacity {
+                // Migration always finishes before the table grows again
+                assert!(!resizing, "grew at {i} before the last resize finished");
+                assert!(map.is_resizing());
+                started = Some((i, capacity));
+            } else if resizing && !map.is_resizing() {
+                // No single insert moves a large share of the old table
+                let (start, old_capacity) = started.take().unwrap();
+                assert!(
+                    i - start >= old_capacity as u32 / 8,
+                    "migrated {old_capacity} entries in {} inserts",
+                    i - start
+                );
+                resizes += 1;
+            }
+        }
+        assert!(resizes >= 10);
+        assert!((0..300_000u32).all(|k| map.get(&k) == Some(&k)));
+    }
+}
+
+#[test]
+fn test_map_incremental_compaction() {
+    let mut map = CustomHashMapBuilder::new()
+        .initial_capacity(1_000)
+        .deletion_strategy(DeletionStrategy::Tombstone)
+        .resize_strategy(ResizeStrategy::Incremental { min_batch: 1 })
+        .build();
+    for i in 0..1_000u32 {
+        map.insert(i, i);
+    }
+    for i in 0..600 {
+        map.remove(&i);
+    }
+    let capacity = map.capacity();
+
+    // Past the tombstone threshold, the next insert starts moving the entries
+    // into a fresh table of the same size rather than rehashing them all
+    map.insert(1_000, 1_000);
+    assert!(map.is_resizing());
+    assert_eq!(map.capacity(), capacity);
+
+    let mut next = 1_001;
+    while map.is_resizing() {
+        assert!((600..next).all(|k| map.get(&k) == Some(&k)));
+        map.insert(next, next);
+        next += 1;
+    }
+    assert!(
+        next > 1_100,
+        "compaction finished after {} inserts",
+        next - 1_000
+    );
+    assert_eq!(map.capacity(), capacity);
+    assert_eq!(map.len(), next as usize - 600);
+    assert!((600..next).all(|k| map.get(&k) == Some(&k)));
+}
+
+#[test]
+#[should_panic(expected = "min_batch must be at least 1")]
+fn test_map_incremental_resize_rejects_zero_min_batch() {
+    let _ =
+        CustomHashMapBuilder::new().resize_strategy(ResizeStrategy::Incremental { min_batch: 0 });
+}
+
+#[test]
+fn test_map_structural_clone() {
+    use std::cell::Cell;
+
+    /// Identity hasher that counts its calls
+    #[derive(Clone)]
+    struct CountingHasher<'a>(&'a Cell<usize>);
+
+    impl KeyHasher<u32> for CountingHasher<'_> {
+        fn hash_key(&self, key: &u32) -> u32 {
+            self.0.set(self.0.get() + 1);
+            *key
+        }
+    }
+
+    let calls = Cell::new(0);
+    let mut map = CustomHashMapBuilder::new()
+        .tombstone_threshold(0.9)
+        .deletion_strategy(DeletionStrategy::Tombstone)
+        .build_with_hasher(CountingHasher(&calls));
+    for i in 0..100 {
+        map.insert(i, i.to_string());
+    }
+    for i in (0..100).step_by(3) {
+        map.remove(&i);
+    }
+    calls.set(0);
+
+    // A plain clone keeps the layout, tombstones included
+    let copy = map.clone();
+    assert_eq!(calls.get(), 0);
+    assert_eq!(copy.capacity(), map.capacity());
+    assert!(copy.iter().eq(map.iter()));
+
+    // The compacted clone re-places the entries without the tombstones
+    let compacted = map.clone_compacted();
+    assert_eq!(calls.get(), 0);
+    assert_eq!(compacted, map);
+    assert!((0..100).all(|i| compacted.contains_key(&i) == (i % 3 != 0)));
+
+    // clone_from overwrites a differently shaped map
+    let mut target = CustomHashMap::with_hasher(CountingHasher(&calls));
+    target.insert(500, "stale".to_string());
+    calls.set(0);
+    target.clone_from(&map);
+    assert_eq!(calls.get(), 0);
+    assert!(target.iter().eq(map.iter()));
+    assert!(!target.contains_key(&500));
+    target.insert(500, "fresh".to_string());
+    assert!(!map.contains_key(&500));
+
+    // Mid-resize clones carry the old table along, or merge it when compacted
+    let mut growing = CustomHashMapBuilder::new()
+        .resize_strategy(ResizeStrategy::Incremental { min_batch: 1 })
+        .build();
+    while !growing.is_resizing() {
+        let next = growing.len() as u32;
+        growing.insert(next, next);
+    }
+    let copy = growing.clone();
+    assert!(copy.is_resizing());
+    assert_eq!(copy, growing);
+    let compacted = growing.clone_compacted();
+    assert!(!compacted.is_resizing());
+    assert_eq!(compacted, growing);
+}
+
+#[test]
+fn test_map_byte_and_shared_string_keys() {
+    use std::borrow::Cow;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let text = "content-type";
+    let hash = assembly_hash_bytes(text.as_bytes());
+    assert_eq!(hash, text.assembly_hash());
+    assert_eq!(assembly_hash_bytes(b"ab"), 80);
+
+    // Every owned, shared and borrowed form hashes like its str or [u8]
+    let bytes = text.as_bytes();
+    assert_eq!(bytes.assembly_hash(), hash);
+    assert_eq!((&bytes).assembly_hash(), hash);
+    assert_eq!(bytes.to_vec().assembly_hash(), hash);
+    assert_eq!(b"content-type".assembly_hash(), hash);
+    assert_eq!(Box::<str>::from(text).assembly_hash(), hash);
+    assert_eq!(Rc::<str>::from(text).assembly_hash(), hash);
+    assert_eq!(Arc::<str>::from(text).assembly_hash(), hash);
+    assert_eq!(Cow::Borrowed(text).assembly_hash(), hash);
+    assert_eq!(Cow::<str>::Owned(text.to_string()).assembly_hash(), hash);
+    assert_eq!(
+        Arc::<str>::from(text).full_content_hash(),
+        bytes.full_content_hash()
+    );
+
+    // Binary protocol keys, looked up by slice
+    let mut opcodes: CustomHashMap<Vec<u8>, u8> = CustomHashMap::new();
+    opcodes.insert(b"\x00\x01PING".to_vec(), 1);
+    opcodes.insert(b"\x00\x02PONG".to_vec(), 2);
+    assert_eq!(opcodes.get(&b"\x00\x02PONG"[..]), Some(&2));
+
+    let mut frames: CustomHashMap<[u8; 4], &str> = CustomHashMap::new();
+    frames.insert(*b"RIFF", "wav");
+    frames.insert(*b"\x89PNG", "png");
+    assert_eq!(frames.get(&b"\x89PNG"[..]), Some(&"png"));
+    assert_eq!(frames.get(b"GIF8"), None);
+
+    // Shared strings, looked up by str
+    let interned: Vec<Rc<str>> = ["alpha", "beta", "gamma"].map(Rc::from).to_vec();
+    let mut cache = LRUCache::new(2);
+    for name in &interned {
+        cache.insert(Rc::clone(name), name.len());
+    }
+    assert_eq!(cache.get("gamma"), Some(&5));
+    assert!(!cache.contains("alpha"));
+
+    let mut shared: CustomHashMap<Arc<str>, u32> = CustomHashMap::new();
+    shared.insert(Arc::from("delta"), 4);
+    assert_eq!(shared.get("delta"), Some(&4));
+    let mut cows: CustomHashMap<Cow<str>, u32> = CustomHashMap::new();
+    cows.insert(Cow::Borrowed("epsilon"), 5);
+    cows.insert(Cow::Owned("zeta".to_string()), 6);
+    assert_eq!(cows.get("zeta"), Some(&6));
+    let boxed: CustomHashMap<Box<str>, u32> = CustomHashMap::from([(Box::from("eta"), 7)]);
+    assert_eq!(boxed.get("eta"), Some(&7));
+}
@@ -0,0 +1,74 @@
+use custom_hashmap::*;
+
+fn sorted<'a>(iter: impl Iterator<Item = &'a u32>) -> Vec<u32> {
+    let mut members: Vec<_> = iter.copied().collect();
+    members.sort_unstable();
+    members
+}
+
+#[test]
+fn test_set_insert_contains_remove() {
+    let mut keywords = CustomHashSet::new();
+    assert!(keywords.insert("while".to_string()));
+    assert!(keywords.insert("return".to_string()));
+    assert!(!keywords.insert("while".to_string()));
+    assert_eq!(keywords.len(), 2);
+
+    assert!(keywords.contains("while"));
+    assert!(!keywords.contains("whale"));
+    assert_eq!(keywords.get("return").map(String::as_str), Some("return"));
+
+    assert!(keywords.remove("while"));
+    assert!(!keywords.remove("while"));
+    assert_eq!(keywords.take("return"), Some("return".to_string()));
+    assert!(keywords.is_empty());
+}
+
+#[test]
+fn test_set_algebra() {
+    let a: CustomHashSet<u32> = (0..10).collect();
+    let b: CustomHashSet<u32> = (5..15).collect();
+
+    assert_eq!(sorted(a.union(&b)), (0..15).collect::<Vec<_>>());
+    assert_eq!(sorted(a.intersection(&b)), (5..10).collect::<Vec<_>>());
+    assert_eq!(sorted(b.intersection(&a)), (5..10).collect::<Vec<_>>());
+    assert_eq!(sorted(a.difference(&b)), (0..5).collect::<Vec<_>>());
+    assert_eq!(
+        sorted(a.symmetric_difference(&b)),
+        (0..5).chain(10..15).collect::<Vec<_>>()
+    );
+
+    // The iterators are lazy and stop as soon as the caller does
+    assert_eq!(a.union(&b).take(3).count(), 3);
+}
+
+#[test]
+fn test_set_relations() {
+    let small = CustomHashSet::from([1u32, 2]);
+    let large = CustomHashSet::from([1u32, 2, 3]);
+    let other = CustomHashSet::from([7u32, 8]);
+
+    assert!(small.is_subset(&large));
+    assert!(!large.is_subset(&small));
+    assert!(large.is_superset(&small));
+    assert!(small.is_disjoint(&other));
+    assert!(!small.is_disjoint(&large));
+    assert!(CustomHashSet::<u32>::new().is_subset(&small));
+
+    assert_eq!(small, CustomHashSet::from([2, 1]));
+    assert_ne!(small, large);
+    assert_eq!(format!("{:?}", CustomHashSet::from([4u32])), "{4}");
+}
+
+#[test]
+fn test_set_retain_and_into_iter() {
+    let mut set: CustomHashSet<u32, MultiplicativeHash> = (0..100).map(|i| i * 32).collect();
+    set.retain(|member| member % 64 == 0);
+    assert_eq!(set.len(), 50);
+
+    let copy = set.clone();
+    let mut members: Vec<_> = set.into_iter().collect();
+    members.sort_unstable();
+    assert_eq!(members, (0..50).map(|i| i * 64).collect::<Vec<_>>());
+    assert_eq!(copy.iter().len(), 50);
+}
@@ -0,0 +1,126 @@
+use custom_hashmap::*;
+
+#[test]
+fn test_indexmap_preserves_insertion_order() {
+    let mut config = CustomIndexMap::new();
+    for key in ["zeta", "alpha", "mid", "beta"] {
+        config.insert(key.to_string(), key.len());
+    }
+    assert_eq!(config.insert("alpha".to_string(), 0), Some(5));
+
+    let keys: Vec<_> = config.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["zeta", "alpha", "mid", "beta"]);
+    assert_eq!(config.get("alpha"), Some(&0));
+    assert_eq!(config.get_index_of("mid"), Some(2));
+    assert_eq!(config.get_index(3), Some((&"beta".to_string(), &4)));
+    assert_eq!(config.first().map(|(k, _)| k.as_str()), Some("zeta"));
+    assert_eq!(config.last().map(|(k, _)| k.as_str()), Some("beta"));
+    assert_eq!(config[1], 0);
+    assert_eq!(config.insert_full("new".to_string(), 3), (4, None));
+
+    let rendered: Vec<_> = config
+        .iter()
+        .rev()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect();
+    assert_eq!(rendered, ["new=3", "beta=4", "mid=3", "alpha=0", "zeta=4"]);
+}
+
+#[test]
+fn test_indexmap_swap_and_shift_remove() {
+    let mut map: CustomIndexMap<u32, char> = (0..5).zip(['a', 'b', 'c', 'd', 'e']).collect();
+
+    // swap_remove moves the last entry into the hole
+    assert_eq!(map.swap_remove(&1), Some('b'));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 4, 2, 3]);
+    assert_eq!(map.get_index_of(&4), Some(1));
+
+    // shift_remove keeps the remaining order
+    assert_eq!(map.shift_remove(&4), Some('e'));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 2, 3]);
+    assert_eq!(map.get_index_of(&3), Some(2));
+    assert_eq!(map.get(&3), Some(&'d'));
+
+    assert_eq!(map.shift_remove_index(0), Some((0, 'a')));
+    assert_eq!(map.swap_remove_index(5), None);
+    assert_eq!(map.pop(), Some((3, 'd')));
+    assert_eq!(map.len(), 1);
+    assert!(!map.contains_key(&3));
+    assert_eq!(map.get_index_of(&2), Some(0));
+}
+
+#[test]
+fn test_indexmap_retain_and_sort() {
+    let mut map: CustomIndexMap<u32, u32> = (0..20).rev().map(|i| (i, i * i)).collect();
+    map.retain(|k, _| k % 3 == 0);
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        [18, 15, 12, 9, 6, 3, 0]
+    );
+    assert_eq!(map.get_index_of(&0), Some(6));
+
+    map.sort_keys();
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        [0, 3, 6, 9, 12, 15, 18]
+    );
+    assert_eq!(map.get_index_of(&18), Some(6));
+    assert_eq!(map.get(&9), Some(&81));
+
+    for (_, value) in &mut map {
+        *value += 1;
+    }
+    assert_eq!(map.values().copied().collect::<Vec<_>>()[..2], [1, 10]);
+    assert_eq!(
+        format!("{:?}", map.clone().into_iter().next()),
+        "Some((0, 1))"
+    );
+}
+
+#[test]
+fn test_indexmap_stores_keys_once() {
+    use std::cell::Cell;
+
+    /// Key without `Clone`, so the map can only keep the one it was given
+    #[derive(Debug, PartialEq, Eq)]
+    struct Name(String);
+
+    /// Hashes by length and counts its calls
+    struct CountingHasher<'a>(&'a Cell<usize>);
+
+    impl KeyHasher<Name> for CountingHasher<'_> {
+        fn hash_key(&self, key: &Name) -> u32 {
+            self.0.set(self.0.get() + 1);
+            key.0.len() as u32
+        }
+    }
+
+    let calls = Cell::new(0);
+    let mut map = CustomIndexMap::with_hasher(CountingHasher(&calls));
+    let names = ["ab", "cd", "efg", "hi", "jklm", "no", "pqr"];
+    for (i, name) in names.iter().enumerate() {
+        assert_eq!(map.insert_full(Name(name.to_string()), i), (i, None));
+    }
+    // One hash per insert, and positions are placed by that cached hash
+    assert_eq!(calls.get(), names.len());
+    assert_eq!(map.insert(Name("cd".to_string()), 10), Some(1));
+    assert_eq!(map.get_index_of(&Name("no".to_string())), Some(5));
+
+    assert_eq!(map.swap_remove(&Name("ab".to_string())), Some(0));
+    assert_eq!(map.get_index_of(&Name("pqr".to_string())), Some(0));
+    assert_eq!(map.shift_remove(&Name("efg".to_string())), Some(2));
+    assert_eq!(map.shift_remove_index(2), Some((Name("hi".to_string()), 3)));
+    let keys: Vec<_> = map.keys().map(|k| k.0.as_str()).collect();
+    assert_eq!(keys, ["pqr", "cd", "jklm", "no"]);
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(map.get_index_of(&Name(key.to_string())), Some(i));
+    }
+
+    map.sort_by(|k1, _, k2, _| k1.0.cmp(&k2.0));
+    map.retain(|k, _| k.0 != "jklm");
+    assert_eq!(map.pop(), Some((Name("pqr".to_string()), 6)));
+    assert_eq!(map.get(&Name("cd".to_string())), Some(&10));
+    assert_eq!(map.get_index_of(&Name("no".to_string())), Some(1));
+    assert!(!map.contains_key(&Name("jklm".to_string())));
+    assert_eq!(map.len(), 2);
+}
@@ -0,0 +1,62 @@
+use custom_hashmap::*;
+
+#[test]
+fn test_multimap_insert_and_get_all() {
+    let mut overloads = CustomMultiMap::new();
+    overloads.insert("print".to_string(), "print(i32)");
+    overloads.insert("print".to_string(), "print(&str)");
+    overloads.insert("len".to_string(), "len(&str)");
+
+    assert_eq!(overloads.len(), 3);
+    assert_eq!(overloads.keys_len(), 2);
+    assert_eq!(overloads.get_all("print"), ["print(i32)", "print(&str)"]);
+    assert_eq!(overloads.get("print"), Some(&"print(i32)"));
+    assert!(overloads.get_all("missing").is_empty());
+    assert_eq!(overloads.get("missing"), None);
+
+    for value in overloads.get_all_mut("len") {
+        *value = "len(&[T])";
+    }
+    assert_eq!(overloads.get_all("len"), ["len(&[T])"]);
+    assert_eq!(overloads.iter().len(), 3);
+}
+
+#[test]
+fn test_multimap_removal_drops_empty_keys() {
+    let mut map: CustomMultiMap<u32, u32> = [(1, 10), (1, 11), (2, 20)].into_iter().collect();
+
+    assert_eq!(map.remove_one(&1, &99), None);
+    assert_eq!(map.remove_one(&1, &10), Some(10));
+    assert_eq!(map.remove_one(&1, &11), Some(11));
+    assert!(!map.contains_key(&1));
+    assert_eq!(map.keys_len(), 1);
+    assert_eq!(map.len(), 1);
+
+    map.extend([(2, 21), (3, 30)]);
+    assert_eq!(map.remove_all(&2), vec![20, 21]);
+    assert!(map.remove_all(&2).is_empty());
+    assert_eq!(map.len(), 1);
+
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.keys_len(), 0);
+}
+
+#[test]
+fn test_multimap_retain_and_iter() {
+    let mut map = CustomMultiMap::new();
+    for i in 0..30u32 {
+        map.insert(i % 3, i);
+    }
+    map.retain(|key, value| *key != 0 && *value % 2 == 1);
+    assert_eq!(map.keys_len(), 2);
+    assert_eq!(map.len(), 10);
+    assert_eq!(map.get_all(&1), [1, 7, 13, 19, 25]);
+
+    let mut pairs: Vec<_> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    pairs.sort_unstable();
+    assert_eq!(pairs.len(), 10);
+    assert_eq!(pairs[0], (1, 1));
+    assert_eq!(map.iter_all().count(), 2);
+    assert_eq!(map.clone().len(), 10);
+}
@@ -0,0 +1,37 @@
+// @generated by custom_hashmap::codegen. Do not edit.
+
+static HEADERS: [Option<(&str, Header)>; 16] = [
+    Some(("cookie", Header::Cookie)),
+    None,
+    Some(("referer", Header::Referer)),
+    None,
+    Some(("host", Header::Host)),
+    Some(("authorization", Header::Authorization)),
+    Some(("origin", Header::Origin)),
+    Some(("user-agent", Header::UserAgent)),
+    Some(("cache-control", Header::CacheControl)),
+    Some(("etag", Header::Etag)),
+    Some(("content-type", Header::ContentType)),
+    None,
+    None,
+    None,
+    None,
+    Some(("accept", Header::Accept)),
+];
+
+pub fn header(key: &str) -> Option<Header> {
+    let bytes = key.as_bytes();
+    let len = bytes.len();
+    let hash = if len < 4 {
+        80
+    } else {
+        let head = u16::from_le_bytes([bytes[0], bytes[1]]) as u32;
+        let tail = u16::from_le_bytes([bytes[len - 2], bytes[len - 1]]) as u32;
+        head << 16 | tail
+    };
+    let idx = ((hash as u64).wrapping_mul(0xf16952ee72f3454f) >> 60) as usize;
+    match HEADERS[idx] {
+        Some((keyword, value)) if keyword == key => Some(value),
+        _ => None,
+    }
+}
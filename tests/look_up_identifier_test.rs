@@ -0,0 +1,51 @@
+use custom_hashmap::AssemblyHash;
+use custom_hashmap::hash::{look_up_identifier, look_up_identifier_portable};
+
+fn both(data: &[u8]) -> (u32, u32) {
+    let fast = unsafe { look_up_identifier(data.as_ptr(), data.len()) };
+    (fast, look_up_identifier_portable(data))
+}
+
+#[test]
+fn test_look_up_identifier_matches_portable_for_all_lengths() {
+    let buffer: Vec<u8> = (0..=255).collect();
+    for len in 0..64 {
+        // Every start offset, so loads are tried at each alignment
+        for start in 0..=buffer.len() - len {
+            let data = &buffer[start..start + len];
+            let (fast, portable) = both(data);
+            assert_eq!(fast, portable, "len {len} at offset {start}");
+        }
+
+        // Every byte value in each of the four bytes the hash reads
+        for byte in 0..=255u8 {
+            for pos in [0, 1, len.saturating_sub(2), len.saturating_sub(1)] {
+                let mut data = vec![b'x'; len];
+                if let Some(slot) = data.get_mut(pos) {
+                    *slot = byte;
+                }
+                let (fast, portable) = both(&data);
+                assert_eq!(fast, portable, "len {len}, byte {byte:#x} at {pos}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_look_up_identifier_reference_values() {
+    assert_eq!(look_up_identifier_portable(b""), 80);
+    assert_eq!(look_up_identifier_portable(b"ab"), 80);
+    assert_eq!(look_up_identifier_portable(b"abc"), 80);
+    assert_eq!(look_up_identifier_portable(b"abcd"), 0x6261_6463);
+    assert_eq!(look_up_identifier_portable(b"hello"), 1_701_343_084);
+    assert_eq!("hello".assembly_hash(), 1_701_343_084);
+    assert_eq!("ab".assembly_hash(), 80);
+}
+
+#[test]
+fn test_look_up_identifier_accepts_any_pointer_for_empty_input() {
+    // A zero-length read is valid for every pointer, null and dangling included
+    assert_eq!(unsafe { look_up_identifier(std::ptr::null(), 0) }, 80);
+    let dangling = std::ptr::NonNull::<u8>::dangling().as_ptr();
+    assert_eq!(unsafe { look_up_identifier(dangling, 0) }, 80);
+}
@@ -0,0 +1,95 @@
+use custom_hashmap::perfect::SearchBounds;
+use custom_hashmap::*;
+
+const HEADERS: [&str; 10] = [
+    "accept",
+    "authorization",
+    "cache-control",
+    "content-type",
+    "cookie",
+    "host",
+    "user-agent",
+    "referer",
+    "origin",
+    "etag",
+];
+
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+#[test]
+fn test_perfect_map_headers() {
+    let map = PerfectHashMap::new(HEADERS.iter().enumerate().map(|(i, &h)| (h, i))).unwrap();
+    assert_eq!(map.len(), HEADERS.len());
+    assert!(map.table_len().is_power_of_two());
+    assert!(map.table_len() >= HEADERS.len());
+    assert_eq!(map.seed() % 2, 1);
+
+    for (i, header) in HEADERS.iter().enumerate() {
+        assert_eq!(map.get(header), Some(&i));
+    }
+    // Misses land on an occupied or empty slot and fail the key comparison
+    for miss in ["accepts", "hast", "x-request-id", "", "et"] {
+        assert!(!map.contains_key(&miss));
+    }
+
+    let mut keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+    keys.sort_unstable();
+    let mut expected = HEADERS;
+    expected.sort_unstable();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn test_perfect_map_keywords_need_full_content_hash() {
+    // Keywords under 4 bytes all share the default hash
+    let entries = || KEYWORDS.iter().map(|k| (k.to_string(), k.len()));
+    let err = PerfectHashMap::new(entries()).unwrap_err();
+    assert!(matches!(err, PerfectHashError::HashCollision { .. }));
+
+    let map = PerfectHashMap::with_hasher(entries(), FullContentHash).unwrap();
+    for keyword in KEYWORDS {
+        assert_eq!(map.get(keyword), Some(&keyword.len()));
+    }
+    assert!(!map.contains_key("mutable"));
+    assert!(!map.contains_key("selF"));
+}
+
+#[test]
+fn test_perfect_map_errors() {
+    let err =
+        PerfectHashMap::new([("content-location", 1), ("host", 2), ("connection", 3)]).unwrap_err();
+    assert_eq!(
+        err,
+        PerfectHashError::HashCollision {
+            first: 0,
+            second: 2
+        }
+    );
+
+    let err = PerfectHashMap::new([("host", 1), ("etag", 2), ("host", 3)]).unwrap_err();
+    assert_eq!(
+        err,
+        PerfectHashError::DuplicateKey {
+            first: 0,
+            second: 2
+        }
+    );
+    assert_eq!(err.to_string(), "entries 0 and 2 have the same key");
+
+    let bounds = SearchBounds {
+        max_slots_per_key: 1,
+        seeds_per_size: 0,
+    };
+    let err = PerfectHashMap::with_bounds(HEADERS.map(|h| (h, ())), AssemblyBuildHash, bounds)
+        .unwrap_err();
+    assert_eq!(err, PerfectHashError::NotFound(bounds));
+
+    let empty = PerfectHashMap::<&str, ()>::new([]).unwrap();
+    assert!(empty.is_empty());
+    assert!(!empty.contains_key(&"host"));
+}